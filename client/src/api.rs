@@ -1,17 +1,165 @@
-use gloo_net::http::Request;
-use platform_core::{Todo, TodoCreate, TodoUpdate, ComponentSetting, ComponentSettingUpdate, Theme, ThemeWithSettings, ThemeCreate, ThemeUpdate};
+use gloo_net::http::{Request, RequestBuilder, Response};
+use leptos::{set_timeout_with_handle, TimeoutHandle};
+use platform_core::{etag, ApiError, ChangeEntity, ChangeEvent, ApiToken, ApiTokenCreate, ApiTokenCreated, LoginRequest, LoginResponse, Assignee, User, Todo, TodoEvent, TodoCreate, TodoUpdate, TodoBulkRequest, TodoBulkResponse, TodoListQuery, TodoSearchHit, TodoSearchQuery, Page, ComponentSetting, Theme, ThemeWithSettings, ThemeCreate, ThemeUpdate};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
 use uuid::Uuid;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{EventSource, EventSourceInit, MessageEvent, RequestCredentials};
 
-const API_BASE: &str = "http://localhost:8080/api";
+use crate::auth;
 
-pub async fn fetch_todos() -> Result<Vec<Todo>, String> {
-    let response = Request::get(&format!("{}/todos", API_BASE))
+const API_ORIGIN: &str = "http://localhost:8080";
+
+/// Server API version every request targets, e.g. `/api/v1/todos`
+pub const API_VERSION: &str = "v1";
+
+fn api_base() -> String {
+    format!("{}/api/{}", API_ORIGIN, API_VERSION)
+}
+
+/// Error returned when an update is rejected because the record changed since it was loaded
+pub const EDIT_CONFLICT: &str = "This record was changed by someone else since it was loaded";
+
+/// HTTP status the server uses for a stale `If-Match` version
+const PRECONDITION_FAILED: u16 = 412;
+
+/// HTTP status the server uses when the session is missing or expired
+const UNAUTHORIZED: u16 = 401;
+
+/// Attaches the stored session token to a request
+fn authorized(builder: RequestBuilder) -> RequestBuilder {
+    match auth::stored_token() {
+        Some(token) => builder.header("Authorization", &format!("Bearer {}", token)),
+        None => builder,
+    }
+}
+
+/// Sends the user back to the login page once the server stops accepting the session
+fn check_session(response: &Response) {
+    if response.status() != UNAUTHORIZED {
+        return;
+    }
+
+    auth::clear_token();
+    if let Some(window) = web_sys::window() {
+        let location = window.location();
+        if location.pathname().map(|path| path != "/login").unwrap_or(true) {
+            let _ = location.set_href("/login");
+        }
+    }
+}
+
+/// Message for a failed response, taken from the server's [`ApiError`] body when it has one
+async fn server_error(response: &Response) -> String {
+    match response.json::<ApiError>().await {
+        Ok(error) => error.to_string(),
+        Err(_) => format!("Server error: {}", response.status()),
+    }
+}
+
+pub async fn fetch_todos(query: &TodoListQuery) -> Result<Page<Todo>, String> {
+    let response = authorized(Request::get(&format!("{}/todos", api_base())))
+        .query(query.to_pairs())
         .send()
         .await
         .map_err(|e| format!("Failed to fetch todos: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+/// Every todo matching `query`, fetched a page of `query.limit` at a time
+pub async fn fetch_all_todos(query: &TodoListQuery) -> Result<Vec<Todo>, String> {
+    let mut query = query.clone();
+    let mut todos = Vec::new();
+    loop {
+        query.offset = Some(todos.len() as i64);
+        let page = fetch_todos(&query).await?;
+        let last = page.items.is_empty() || page.offset + page.items.len() as i64 >= page.total;
+        todos.extend(page.items);
+        if last {
+            return Ok(todos);
+        }
+    }
+}
+
+pub async fn search_todos(query: &TodoSearchQuery) -> Result<Vec<TodoSearchHit>, String> {
+    let mut params = vec![("q", query.q.clone())];
+    if let Some(limit) = query.limit {
+        params.push(("limit", limit.to_string()));
+    }
+
+    let response = authorized(Request::get(&format!("{}/todos/search", api_base())))
+        .query(params)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to search todos: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn fetch_todo(id: Uuid) -> Result<Todo, String> {
+    let response = authorized(Request::get(&format!("{}/todos/{}", api_base(), id)))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch todo: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn fetch_todo_children(id: Uuid) -> Result<Vec<Todo>, String> {
+    let response = authorized(Request::get(&format!("{}/todos/{}/children", api_base(), id)))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch subtasks: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn fetch_todo_history(id: Uuid) -> Result<Vec<TodoEvent>, String> {
+    let response = authorized(Request::get(&format!("{}/todos/{}/history", api_base(), id)))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch todo history: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
     }
 
     response
@@ -21,15 +169,16 @@ pub async fn fetch_todos() -> Result<Vec<Todo>, String> {
 }
 
 pub async fn create_todo(todo: TodoCreate) -> Result<Todo, String> {
-    let response = Request::post(&format!("{}/todos", API_BASE))
+    let response = authorized(Request::post(&format!("{}/todos", api_base())))
         .json(&todo)
         .map_err(|e| format!("Failed to serialize todo: {}", e))?
         .send()
         .await
         .map_err(|e| format!("Failed to create todo: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -38,16 +187,23 @@ pub async fn create_todo(todo: TodoCreate) -> Result<Todo, String> {
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
-pub async fn update_todo(id: Uuid, update: TodoUpdate) -> Result<Todo, String> {
-    let response = Request::put(&format!("{}/todos/{}", API_BASE, id))
+/// Updates a todo, provided it is still at `version`
+pub async fn update_todo(id: Uuid, version: i32, update: TodoUpdate) -> Result<Todo, String> {
+    let response = authorized(Request::put(&format!("{}/todos/{}", api_base(), id)))
+        .header("If-Match", &etag(version))
         .json(&update)
         .map_err(|e| format!("Failed to serialize update: {}", e))?
         .send()
         .await
         .map_err(|e| format!("Failed to update todo: {}", e))?;
+    check_session(&response);
+
+    if response.status() == PRECONDITION_FAILED {
+        return Err(EDIT_CONFLICT.to_string());
+    }
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -57,27 +213,28 @@ pub async fn update_todo(id: Uuid, update: TodoUpdate) -> Result<Todo, String> {
 }
 
 pub async fn delete_todo(id: Uuid) -> Result<(), String> {
-    let response = Request::delete(&format!("{}/todos/{}", API_BASE, id))
+    let response = authorized(Request::delete(&format!("{}/todos/{}", api_base(), id)))
         .send()
         .await
         .map_err(|e| format!("Failed to delete todo: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     Ok(())
 }
 
-// Component Settings API
-pub async fn fetch_settings() -> Result<Vec<ComponentSetting>, String> {
-    let response = Request::get(&format!("{}/settings", API_BASE))
+pub async fn restore_todo(id: Uuid) -> Result<Todo, String> {
+    let response = authorized(Request::post(&format!("{}/todos/{}/restore", api_base(), id)))
         .send()
         .await
-        .map_err(|e| format!("Failed to fetch settings: {}", e))?;
+        .map_err(|e| format!("Failed to restore todo: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -86,16 +243,17 @@ pub async fn fetch_settings() -> Result<Vec<ComponentSetting>, String> {
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
-pub async fn update_setting(id: Uuid, update: ComponentSettingUpdate) -> Result<ComponentSetting, String> {
-    let response = Request::put(&format!("{}/settings/{}", API_BASE, id))
-        .json(&update)
-        .map_err(|e| format!("Failed to serialize update: {}", e))?
+pub async fn bulk_todos(request: TodoBulkRequest) -> Result<TodoBulkResponse, String> {
+    let response = authorized(Request::post(&format!("{}/todos/bulk", api_base())))
+        .json(&request)
+        .map_err(|e| format!("Failed to serialize bulk request: {}", e))?
         .send()
         .await
-        .map_err(|e| format!("Failed to update setting: {}", e))?;
+        .map_err(|e| format!("Failed to apply bulk action: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -106,13 +264,52 @@ pub async fn update_setting(id: Uuid, update: ComponentSettingUpdate) -> Result<
 
 // Themes API
 pub async fn fetch_themes() -> Result<Vec<Theme>, String> {
-    let response = Request::get(&format!("{}/themes", API_BASE))
+    let response = authorized(Request::get(&format!("{}/themes", api_base())))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch themes: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+/// Themes currently in the trash
+pub async fn fetch_deleted_themes() -> Result<Vec<Theme>, String> {
+    let response = authorized(Request::get(&format!("{}/themes", api_base())))
+        .query([("include_deleted", "true")])
         .send()
         .await
         .map_err(|e| format!("Failed to fetch themes: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    let themes: Vec<Theme> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    Ok(themes.into_iter().filter(|theme| theme.deleted_at.is_some()).collect())
+}
+
+pub async fn restore_theme(id: Uuid) -> Result<Theme, String> {
+    let response = authorized(Request::post(&format!("{}/themes/{}/restore", api_base(), id)))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to restore theme: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -122,13 +319,14 @@ pub async fn fetch_themes() -> Result<Vec<Theme>, String> {
 }
 
 pub async fn fetch_theme_with_settings(id: Uuid) -> Result<ThemeWithSettings, String> {
-    let response = Request::get(&format!("{}/themes/{}", API_BASE, id))
+    let response = authorized(Request::get(&format!("{}/themes/{}", api_base(), id)))
         .send()
         .await
         .map_err(|e| format!("Failed to fetch theme: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -138,15 +336,16 @@ pub async fn fetch_theme_with_settings(id: Uuid) -> Result<ThemeWithSettings, St
 }
 
 pub async fn create_theme(theme: ThemeCreate) -> Result<Theme, String> {
-    let response = Request::post(&format!("{}/themes", API_BASE))
+    let response = authorized(Request::post(&format!("{}/themes", api_base())))
         .json(&theme)
         .map_err(|e| format!("Failed to serialize theme: {}", e))?
         .send()
         .await
         .map_err(|e| format!("Failed to create theme: {}", e))?;
+    check_session(&response);
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -155,16 +354,23 @@ pub async fn create_theme(theme: ThemeCreate) -> Result<Theme, String> {
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
-pub async fn update_theme(id: Uuid, update: ThemeUpdate) -> Result<Theme, String> {
-    let response = Request::put(&format!("{}/themes/{}", API_BASE, id))
+/// Updates a theme, provided it is still at `version`
+pub async fn update_theme(id: Uuid, version: i32, update: ThemeUpdate) -> Result<Theme, String> {
+    let response = authorized(Request::put(&format!("{}/themes/{}", api_base(), id)))
+        .header("If-Match", &etag(version))
         .json(&update)
         .map_err(|e| format!("Failed to serialize update: {}", e))?
         .send()
         .await
         .map_err(|e| format!("Failed to update theme: {}", e))?;
+    check_session(&response);
+
+    if response.status() == PRECONDITION_FAILED {
+        return Err(EDIT_CONFLICT.to_string());
+    }
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -173,16 +379,28 @@ pub async fn update_theme(id: Uuid, update: ThemeUpdate) -> Result<Theme, String
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
 
-pub async fn update_theme_setting(theme_id: Uuid, setting_id: Uuid, value: String) -> Result<ComponentSetting, String> {
-    let response = Request::put(&format!("{}/themes/{}/settings/{}", API_BASE, theme_id, setting_id))
+/// Updates a theme setting, provided it is still at `version`
+pub async fn update_theme_setting(
+    theme_id: Uuid,
+    setting_id: Uuid,
+    version: i32,
+    value: String,
+) -> Result<ComponentSetting, String> {
+    let response = authorized(Request::put(&format!("{}/themes/{}/settings/{}", api_base(), theme_id, setting_id)))
+        .header("If-Match", &etag(version))
         .json(&value)
         .map_err(|e| format!("Failed to serialize value: {}", e))?
         .send()
         .await
         .map_err(|e| format!("Failed to update setting: {}", e))?;
+    check_session(&response);
+
+    if response.status() == PRECONDITION_FAILED {
+        return Err(EDIT_CONFLICT.to_string());
+    }
 
     if !response.ok() {
-        return Err(format!("Server error: {}", response.status()));
+        return Err(server_error(&response).await);
     }
 
     response
@@ -190,3 +408,247 @@ pub async fn update_theme_setting(theme_id: Uuid, setting_id: Uuid, value: Strin
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
+
+pub async fn login(request: LoginRequest) -> Result<LoginResponse, String> {
+    // Keeps the session cookie as well, which the change feed signs in with
+    let response = Request::post(&format!("{}/auth/login", api_base()))
+        .credentials(RequestCredentials::Include)
+        .json(&request)
+        .map_err(|e| format!("Failed to serialize login: {}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to sign in: {}", e))?;
+
+    if response.status() == UNAUTHORIZED {
+        return Err("Invalid email or password".to_string());
+    }
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn logout() -> Result<(), String> {
+    // Sends the session cookie so the server can clear it
+    let response = authorized(Request::post(&format!("{}/auth/logout", api_base())))
+        .credentials(RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to sign out: {}", e))?;
+
+    // An expired session is as good as signed out
+    if !response.ok() && response.status() != UNAUTHORIZED {
+        return Err(server_error(&response).await);
+    }
+
+    Ok(())
+}
+
+/// Every account's id and display name
+pub async fn fetch_assignees() -> Result<Vec<Assignee>, String> {
+    let response = authorized(Request::get(&format!("{}/assignees", api_base())))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch users: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn fetch_current_user() -> Result<User, String> {
+    let response = authorized(Request::get(&format!("{}/auth/me", api_base())))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch current user: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn fetch_tokens() -> Result<Vec<ApiToken>, String> {
+    let response = authorized(Request::get(&format!("{}/tokens", api_base())))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch API tokens: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn create_token(token: ApiTokenCreate) -> Result<ApiTokenCreated, String> {
+    let response = authorized(Request::post(&format!("{}/tokens", api_base())))
+        .json(&token)
+        .map_err(|e| format!("Failed to serialize token: {}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to create API token: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+pub async fn revoke_token(id: Uuid) -> Result<(), String> {
+    let response = authorized(Request::delete(&format!("{}/tokens/{}", api_base(), id)))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to revoke API token: {}", e))?;
+    check_session(&response);
+
+    if !response.ok() {
+        return Err(server_error(&response).await);
+    }
+
+    Ok(())
+}
+
+/// Changes arriving this close together cause one reload
+const CHANGE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// What changed on the server since the last call of a [`watch_changes`] callback
+#[derive(Debug, Clone)]
+pub enum Changes {
+    /// These records, oldest change first
+    Records(Vec<ChangeEvent>),
+    /// Anything; the feed reconnected and changes made meanwhile are not replayed
+    Unknown,
+}
+
+/// Open connection to the live change feed; closed when dropped
+pub struct ChangeFeed {
+    source: EventSource,
+    pending: Rc<Cell<Option<TimeoutHandle>>>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_open: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Drop for ChangeFeed {
+    fn drop(&mut self) {
+        self.source.close();
+        if let Some(handle) = self.pending.take() {
+            handle.clear();
+        }
+    }
+}
+
+/// Calls `on_change` whenever a record of one of `entities` changes on the server
+///
+/// Bursts of changes, such as a bulk action, cause one call with all of them.
+/// It is also called with [`Changes::Unknown`] after the browser reconnects a
+/// dropped feed. Returns `None` when signed out.
+pub fn watch_changes(
+    entities: &'static [ChangeEntity],
+    on_change: impl Fn(Changes) + 'static,
+) -> Option<ChangeFeed> {
+    if auth::stored_token().is_none() {
+        return None;
+    }
+    // EventSource cannot send an Authorization header, so it relies on the
+    // session cookie set at sign-in
+    let init = EventSourceInit::new();
+    init.set_with_credentials(true);
+    let source = EventSource::new_with_event_source_init_dict(&format!("{}/events", api_base()), &init)
+        .map_err(|e| log::error!("Failed to open the change feed: {:?}", e))
+        .ok()?;
+
+    let pending = Rc::new(Cell::new(None::<TimeoutHandle>));
+    // Collected until the debounce fires; `None` once a reconnect made them moot
+    let collected = Rc::new(RefCell::new(Some(Vec::new())));
+    let schedule = {
+        let pending = pending.clone();
+        let collected = collected.clone();
+        let on_change = Rc::new(on_change);
+        move |event: Option<ChangeEvent>| {
+            {
+                let mut collected = collected.borrow_mut();
+                match (collected.as_mut(), event) {
+                    (Some(events), Some(event)) => events.push(event),
+                    (_, None) => *collected = None,
+                    (None, Some(_)) => {}
+                }
+            }
+            if pending.get().is_some() {
+                return;
+            }
+            let on_change = on_change.clone();
+            let collected = collected.clone();
+            let fired = pending.clone();
+            let handle = set_timeout_with_handle(
+                move || {
+                    fired.set(None);
+                    let changes = match collected.replace(Some(Vec::new())) {
+                        Some(events) => Changes::Records(events),
+                        None => Changes::Unknown,
+                    };
+                    on_change(changes);
+                },
+                CHANGE_DEBOUNCE,
+            );
+            pending.set(handle.ok());
+        }
+    };
+
+    let on_message = {
+        let schedule = schedule.clone();
+        Closure::<dyn FnMut(MessageEvent)>::new(move |message: MessageEvent| {
+            let Some(data) = message.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str::<ChangeEvent>(&data) {
+                Ok(event) if entities.contains(&event.entity) => schedule(Some(event)),
+                Ok(_) => {}
+                Err(e) => log::warn!("Ignoring change event {:?}: {}", data, e),
+            }
+        })
+    };
+
+    // The first open is the initial connection; later ones are reconnects
+    let opened = Cell::new(false);
+    let on_open = Closure::<dyn FnMut(web_sys::Event)>::new(move |_: web_sys::Event| {
+        if opened.replace(true) {
+            schedule(None);
+        }
+    });
+
+    source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    source.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+    Some(ChangeFeed {
+        source,
+        pending,
+        _on_message: on_message,
+        _on_open: on_open,
+    })
+}
@@ -0,0 +1,66 @@
+use leptos::*;
+use platform_core::{Assignee, User};
+
+const TOKEN_STORAGE_KEY: &str = "atom_session_token";
+
+/// Session token kept in localStorage
+///
+/// The API also sets a session cookie, but the client is served from a
+/// different origin, so requests carry the token as a bearer header instead.
+pub fn stored_token() -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    storage.get_item(TOKEN_STORAGE_KEY).ok()?
+}
+
+pub fn store_token(token: &str) {
+    if let Some(window) = web_sys::window() {
+        if let Ok(Some(storage)) = window.local_storage() {
+            let _ = storage.set_item(TOKEN_STORAGE_KEY, token);
+        }
+    }
+}
+
+pub fn clear_token() {
+    if let Some(window) = web_sys::window() {
+        if let Ok(Some(storage)) = window.local_storage() {
+            let _ = storage.remove_item(TOKEN_STORAGE_KEY);
+        }
+    }
+}
+
+/// The signed-in user, loaded lazily from `/api/auth/me`
+#[derive(Clone, Debug)]
+pub struct AuthContext {
+    /// Whether a session token is stored; change it through `sign_in` and `sign_out`
+    signed_in: RwSignal<bool>,
+    pub user: RwSignal<Option<User>>,
+    /// Every account, for showing and picking assignees
+    pub users: RwSignal<Vec<Assignee>>,
+}
+
+impl AuthContext {
+    pub fn new() -> Self {
+        Self {
+            signed_in: create_rw_signal(stored_token().is_some()),
+            user: create_rw_signal(None),
+            users: create_rw_signal(Vec::new()),
+        }
+    }
+
+    /// Tracked, so effects and views rerun when the user signs in or out
+    pub fn is_signed_in(&self) -> bool {
+        self.signed_in.get()
+    }
+
+    pub fn sign_in(&self, token: &str, user: User) {
+        store_token(token);
+        self.user.set(Some(user));
+        self.signed_in.set(true);
+    }
+
+    pub fn sign_out(&self) {
+        clear_token();
+        self.user.set(None);
+        self.signed_in.set(false);
+    }
+}
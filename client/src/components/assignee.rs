@@ -0,0 +1,90 @@
+use leptos::*;
+use uuid::Uuid;
+
+use crate::auth::AuthContext;
+use crate::components::ui::{initials, Avatar, AvatarGroup, AvatarSize};
+
+/// Avatar of one account, with `label` and the user's name as a tooltip
+#[component]
+pub fn UserAvatar(
+    user_id: Uuid,
+    /// Tooltip prefix, e.g. "Assigned to"
+    #[prop(into)]
+    label: String,
+    #[prop(optional, default = AvatarSize::Sm)] size: AvatarSize,
+) -> impl IntoView {
+    let users = expect_context::<AuthContext>().users;
+
+    move || {
+        let name = users.with(|users| {
+            users
+                .iter()
+                .find(|user| user.id == user_id)
+                .map(|user| user.display_name.clone())
+        });
+        view! {
+            <span title=format!("{} {}", label, name.as_deref().unwrap_or("a removed user"))>
+                <Avatar
+                    initials=name.as_deref().map(initials).unwrap_or_default()
+                    size=size
+                    class="border-2 border-white".to_string()
+                />
+            </span>
+        }
+    }
+}
+
+/// Who is working on a todo, followed by who created it when that is someone else
+#[component]
+pub fn TodoPeople(owner_id: Option<Uuid>, assignee_id: Option<Uuid>) -> impl IntoView {
+    let owner_id = owner_id.filter(|owner| Some(*owner) != assignee_id);
+
+    view! {
+        <div class="flex items-center gap-2">
+            <AvatarGroup>
+                {assignee_id.map(|id| view! { <UserAvatar user_id=id label="Assigned to"/> })}
+                {owner_id.map(|id| view! { <UserAvatar user_id=id label="Created by"/> })}
+            </AvatarGroup>
+            {assignee_id.is_none().then(|| view! {
+                <span class="text-xs text-slate-400">"Unassigned"</span>
+            })}
+        </div>
+    }
+}
+
+/// Drop-down of every account, plus "Unassigned"
+#[component]
+pub fn AssigneeSelect(
+    #[prop(into)] value: Signal<Option<Uuid>>,
+    on_change: Callback<Option<Uuid>>,
+    #[prop(optional, into)] class: String,
+    #[prop(optional, into)] disabled: MaybeSignal<bool>,
+) -> impl IntoView {
+    let users = expect_context::<AuthContext>().users;
+
+    // Options arrive after the select is built, so each one tracks whether it is selected
+    view! {
+        <select
+            on:change=move |ev| on_change.call(event_target_value(&ev).parse().ok())
+            disabled=move || disabled.get()
+            title="Assignee"
+            class=class
+        >
+            <option value="" prop:selected=move || value.get().is_none()>"Unassigned"</option>
+            {move || {
+                users
+                    .get()
+                    .into_iter()
+                    .map(|user| {
+                        let id = user.id;
+                        view! {
+                            <option value=id.to_string() prop:selected=move || value.get() == Some(id)>
+                                {user.display_name}
+                            </option>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </select>
+    }
+}
@@ -0,0 +1,44 @@
+use leptos::*;
+
+use crate::components::ui::{Modal, ModalSize};
+
+/// Shown when a save is rejected because someone else changed the record first
+///
+/// "Reload" discards the local edit in favour of the latest version, while
+/// "Overwrite" re-applies the local edit on top of it. Closing the dialog keeps
+/// the local edit so it can be copied elsewhere.
+#[component]
+pub fn ConflictDialog(
+    #[prop(into)] open: Signal<bool>,
+    /// What was changed, e.g. "todo" or "theme setting"
+    #[prop(into)]
+    subject: String,
+    on_reload: Callback<()>,
+    on_overwrite: Callback<()>,
+    on_close: Callback<()>,
+) -> impl IntoView {
+    view! {
+        <Modal open=open on_close=on_close size=ModalSize::Md title="Edit conflict".to_string()>
+            <p class="text-slate-700 dark:text-slate-300 mb-6">
+                {format!(
+                    "This {} was changed by someone else after you opened it. Reload to see their changes, or overwrite them with yours.",
+                    subject,
+                )}
+            </p>
+            <div class="flex justify-end gap-2">
+                <button
+                    on:click=move |_| on_reload.call(())
+                    class="px-4 py-2 bg-slate-600 text-white rounded-lg font-semibold hover:bg-slate-700 transition-colors"
+                >
+                    "Reload"
+                </button>
+                <button
+                    on:click=move |_| on_overwrite.call(())
+                    class="px-4 py-2 bg-red-600 text-white rounded-lg font-semibold hover:bg-red-700 transition-colors"
+                >
+                    "Overwrite"
+                </button>
+            </div>
+        </Modal>
+    }
+}
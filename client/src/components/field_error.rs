@@ -0,0 +1,22 @@
+use leptos::*;
+use platform_core::{field_error, FieldError};
+
+/// Red hint under a form input for the first error reported against `field`
+///
+/// The errors come from running the `platform_core` validation rules on the
+/// request before sending it, so they match what the server would answer.
+#[component]
+pub fn FieldErrorText(
+    #[prop(into)] errors: Signal<Vec<FieldError>>,
+    field: &'static str,
+    /// Shown in front of the message, e.g. "Title" for "Title is required"
+    label: &'static str,
+) -> impl IntoView {
+    move || {
+        errors.with(|errors| {
+            field_error(errors, field).map(|message| {
+                view! { <p class="mt-1 text-sm text-red-600">{format!("{} {}", label, message)}</p> }
+            })
+        })
+    }
+}
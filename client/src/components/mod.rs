@@ -1,4 +1,9 @@
 pub mod todo_list;
 pub mod todo_item;
 pub mod todo_form;
+pub mod todo_search;
+pub mod todo_history;
+pub mod conflict_dialog;
+pub mod assignee;
+pub mod field_error;
 pub mod ui;
@@ -1,44 +1,85 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use leptos::*;
-use platform_core::{Todo, TodoCreate};
+use platform_core::{normalize_tags, FieldError, Todo, TodoCreate, TodoPriority, Validate};
+use uuid::Uuid;
 
 use crate::api;
+use crate::components::assignee::AssigneeSelect;
+use crate::components::field_error::FieldErrorText;
+
+/// Parses a `YYYY-MM-DD` date input as the end of that day in UTC
+pub fn parse_due_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .map(|datetime| datetime.and_utc())
+}
+
+/// Splits a comma-separated tag input into normalized tag names
+pub fn parse_tags(value: &str) -> Vec<String> {
+    normalize_tags(value.split(','))
+}
+
+/// Parses the value of a priority `<select>`, falling back to the default
+pub fn parse_priority(value: &str) -> TodoPriority {
+    value.parse().unwrap_or_default()
+}
 
 #[component]
-pub fn TodoForm(on_created: Callback<Todo>) -> impl IntoView {
+pub fn TodoForm(
+    on_created: Callback<Todo>,
+    /// Creates subtasks of this todo instead of top-level todos
+    #[prop(optional)]
+    parent_id: Option<Uuid>,
+) -> impl IntoView {
     let (title, set_title) = create_signal(String::new());
     let (description, set_description) = create_signal(String::new());
+    let (priority, set_priority) = create_signal(TodoPriority::default());
+    let (due_date, set_due_date) = create_signal(String::new());
+    let (tags, set_tags) = create_signal(String::new());
+    let (assignee_id, set_assignee_id) = create_signal(None::<Uuid>);
     let (is_creating, set_is_creating) = create_signal(false);
     let (error, set_error) = create_signal(None::<String>);
+    let (field_errors, set_field_errors) = create_signal(Vec::<FieldError>::new());
 
     let handle_submit = move |ev: leptos::ev::SubmitEvent| {
         ev.prevent_default();
 
-        let title_value = title.get();
-        if title_value.trim().is_empty() {
-            set_error.set(Some("Title is required".to_string()));
+        let new_todo = TodoCreate {
+            title: title.get(),
+            description: if description.get().is_empty() {
+                None
+            } else {
+                Some(description.get())
+            },
+            status: None,
+            priority: Some(priority.get()),
+            due_at: parse_due_date(&due_date.get()),
+            tags: parse_tags(&tags.get()),
+            parent_id,
+            assignee_id: assignee_id.get(),
+        };
+        // Same rules as the server, so most mistakes never leave the browser
+        if let Err(fields) = new_todo.validate() {
+            set_field_errors.set(fields);
             return;
         }
 
         let on_created = on_created.clone();
         set_is_creating.set(true);
         set_error.set(None);
+        set_field_errors.set(Vec::new());
 
         spawn_local(async move {
-            let new_todo = TodoCreate {
-                title: title_value,
-                description: if description.get().is_empty() {
-                    None
-                } else {
-                    Some(description.get())
-                },
-                status: None,
-            };
-
             match api::create_todo(new_todo).await {
                 Ok(created_todo) => {
                     on_created.call(created_todo);
                     set_title.set(String::new());
                     set_description.set(String::new());
+                    set_priority.set(TodoPriority::default());
+                    set_due_date.set(String::new());
+                    set_tags.set(String::new());
+                    set_assignee_id.set(None);
                 }
                 Err(e) => {
                     set_error.set(Some(e));
@@ -50,7 +91,9 @@ pub fn TodoForm(on_created: Callback<Todo>) -> impl IntoView {
 
     view! {
         <div class="bg-white p-6 rounded-xl shadow-md mb-8">
-            <h2 class="text-2xl font-semibold mb-6 text-slate-800">"Create New Todo"</h2>
+            <h2 class="text-2xl font-semibold mb-6 text-slate-800">
+                {if parent_id.is_some() { "Add Subtask" } else { "Create New Todo" }}
+            </h2>
             <form on:submit=handle_submit class="space-y-4">
                 <div>
                     <input
@@ -62,6 +105,7 @@ pub fn TodoForm(on_created: Callback<Todo>) -> impl IntoView {
                         style="border-color: #e2e8f0;"
                         class="w-full px-4 py-3 border-2 rounded-lg focus:outline-none transition-colors disabled:opacity-60 disabled:cursor-not-allowed focus:border-primary"
                     />
+                    <FieldErrorText errors=field_errors field="title" label="Title"/>
                 </div>
                 <div>
                     <textarea
@@ -72,7 +116,47 @@ pub fn TodoForm(on_created: Callback<Todo>) -> impl IntoView {
                         style="border-color: #e2e8f0;"
                         class="w-full px-4 py-3 border-2 rounded-lg focus:outline-none transition-colors min-h-[100px] resize-y disabled:opacity-60 disabled:cursor-not-allowed focus:border-primary"
                     />
+                    <FieldErrorText errors=field_errors field="description" label="Description"/>
+                </div>
+                <div class="grid grid-cols-1 md:grid-cols-4 gap-4">
+                    <select
+                        on:change=move |ev| set_priority.set(parse_priority(&event_target_value(&ev)))
+                        prop:value=move || priority.get().as_str()
+                        disabled=is_creating
+                        style="border-color: #e2e8f0;"
+                        class="px-4 py-3 border-2 rounded-lg bg-white focus:outline-none disabled:opacity-60"
+                    >
+                        <option value="low">"Low priority"</option>
+                        <option value="medium">"Medium priority"</option>
+                        <option value="high">"High priority"</option>
+                        <option value="urgent">"Urgent"</option>
+                    </select>
+                    <input
+                        type="date"
+                        prop:value=due_date
+                        on:input=move |ev| set_due_date.set(event_target_value(&ev))
+                        disabled=is_creating
+                        title="Due date (optional)"
+                        style="border-color: #e2e8f0;"
+                        class="px-4 py-3 border-2 rounded-lg focus:outline-none disabled:opacity-60"
+                    />
+                    <input
+                        type="text"
+                        prop:value=tags
+                        on:input=move |ev| set_tags.set(event_target_value(&ev))
+                        placeholder="Tags, comma separated"
+                        disabled=is_creating
+                        style="border-color: #e2e8f0;"
+                        class="px-4 py-3 border-2 rounded-lg focus:outline-none disabled:opacity-60"
+                    />
+                    <AssigneeSelect
+                        value=assignee_id
+                        on_change=Callback::new(move |id| set_assignee_id.set(id))
+                        disabled=is_creating
+                        class="px-4 py-3 border-2 border-slate-200 rounded-lg bg-white focus:outline-none disabled:opacity-60"
+                    />
                 </div>
+                <FieldErrorText errors=field_errors field="tags" label="Tags:"/>
                 {move || {
                     error
                         .get()
@@ -87,7 +171,11 @@ pub fn TodoForm(on_created: Callback<Todo>) -> impl IntoView {
                     style="background-color: var(--color-primary);"
                     class="w-full py-3 text-white rounded-lg font-semibold hover:opacity-90 transition-all disabled:opacity-60 disabled:cursor-not-allowed"
                 >
-                    {move || if is_creating.get() { "Creating..." } else { "Create Todo" }}
+                    {move || match (is_creating.get(), parent_id.is_some()) {
+                        (true, _) => "Creating...",
+                        (false, true) => "Add Subtask",
+                        (false, false) => "Create Todo",
+                    }}
                 </button>
             </form>
         </div>
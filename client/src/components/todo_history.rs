@@ -0,0 +1,132 @@
+use leptos::*;
+use platform_core::{TodoEvent, TodoEventKind, TodoFieldChange};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::api;
+
+/// Human-readable form of a history value
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => "—".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.is_empty() => "—".to_string(),
+        Value::Array(items) => items
+            .iter()
+            .map(format_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// Timeline dot colour for each event kind
+fn kind_color(kind: TodoEventKind) -> &'static str {
+    match kind {
+        TodoEventKind::Created => "bg-green-500",
+        TodoEventKind::Updated => "bg-blue-500",
+        TodoEventKind::Deleted => "bg-red-500",
+        TodoEventKind::Restored => "bg-amber-500",
+    }
+}
+
+fn render_change(kind: TodoEventKind, change: TodoFieldChange) -> impl IntoView {
+    let field = change.field.replace('_', " ");
+    let detail = match kind {
+        TodoEventKind::Created => format_value(&change.new),
+        _ => format!(
+            "{} → {}",
+            format_value(&change.old),
+            format_value(&change.new)
+        ),
+    };
+
+    view! {
+        <li class="text-sm text-slate-600">
+            <span class="font-semibold text-slate-700">{field}": "</span>
+            {detail}
+        </li>
+    }
+}
+
+/// Vertical timeline of a todo's audit history, newest first
+#[component]
+pub fn TodoHistory(
+    todo_id: Uuid,
+    /// Bump to refetch after the todo changes
+    #[prop(into)]
+    reload: Signal<u32>,
+) -> impl IntoView {
+    let (events, set_events) = create_signal(Vec::<TodoEvent>::new());
+    let (loading, set_loading) = create_signal(true);
+    let (error, set_error) = create_signal(None::<String>);
+
+    create_effect(move |_| {
+        reload.track();
+        spawn_local(async move {
+            set_loading.set(true);
+            match api::fetch_todo_history(todo_id).await {
+                Ok(fetched) => {
+                    set_events.set(fetched);
+                    set_error.set(None);
+                }
+                Err(e) => {
+                    set_error.set(Some(e));
+                }
+            }
+            set_loading.set(false);
+        });
+    });
+
+    view! {
+        <div class="bg-white p-6 rounded-xl shadow-md">
+            <h3 class="text-xl font-semibold text-slate-800 mb-4">"History"</h3>
+            {move || {
+                if let Some(err) = error.get() {
+                    view! { <p class="text-red-600">"Error: " {err}</p> }.into_view()
+                } else if loading.get() && events.with(Vec::is_empty) {
+                    view! { <p class="text-slate-500">"Loading history..."</p> }.into_view()
+                } else if events.with(Vec::is_empty) {
+                    view! { <p class="text-slate-500">"No recorded changes yet."</p> }.into_view()
+                } else {
+                    view! {
+                        <ol class="relative border-l-2 border-slate-200 ml-2 space-y-6">
+                            {events
+                                .get()
+                                .into_iter()
+                                .rev()
+                                .map(|event| {
+                                    let kind = event.kind;
+                                    view! {
+                                        <li class="ml-6">
+                                            <span class=format!(
+                                                "absolute -left-[7px] mt-1.5 w-3 h-3 rounded-full {}",
+                                                kind_color(kind),
+                                            )></span>
+                                            <div class="flex items-baseline gap-2 mb-1">
+                                                <span class="font-semibold text-slate-800 capitalize">
+                                                    {kind.as_str()}
+                                                </span>
+                                                <time class="text-xs text-slate-500">
+                                                    {event.created_at.format("%Y-%m-%d %H:%M:%S").to_string()}
+                                                </time>
+                                            </div>
+                                            <ul class="space-y-1">
+                                                {event
+                                                    .changes
+                                                    .into_iter()
+                                                    .map(|change| render_change(kind, change))
+                                                    .collect_view()}
+                                            </ul>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()}
+                        </ol>
+                    }
+                    .into_view()
+                }
+            }}
+        </div>
+    }
+}
@@ -1,44 +1,114 @@
 use leptos::*;
-use platform_core::{Todo, TodoStatus, TodoUpdate};
+use leptos_router::A;
+use platform_core::{FieldError, Patch, Todo, TodoPriority, TodoStatus, TodoUpdate, Validate};
 use uuid::Uuid;
 
 use crate::api;
+use crate::components::assignee::{AssigneeSelect, TodoPeople};
+use crate::components::conflict_dialog::ConflictDialog;
+use crate::components::field_error::FieldErrorText;
+use crate::components::todo_form::{parse_due_date, parse_priority, parse_tags};
+use crate::components::ui::{Badge, BadgeSize, BadgeVariant};
+
+/// Button label for moving a todo into `status`
+pub fn status_action_label(status: TodoStatus) -> &'static str {
+    match status {
+        TodoStatus::Pending => "Reopen",
+        TodoStatus::InProgress => "Start",
+        TodoStatus::Completed => "Complete",
+        TodoStatus::Cancelled => "Cancel Todo",
+    }
+}
+
+/// Badge colour used for each priority level
+pub fn priority_variant(priority: TodoPriority) -> BadgeVariant {
+    match priority {
+        TodoPriority::Low => BadgeVariant::Light,
+        TodoPriority::Medium => BadgeVariant::Info,
+        TodoPriority::High => BadgeVariant::Warning,
+        TodoPriority::Urgent => BadgeVariant::Danger,
+    }
+}
 
 #[component]
 pub fn TodoItem(
-    todo: Todo,
+    /// Shown as it changes, unless it is being edited
+    #[prop(into)]
+    todo: MaybeSignal<Todo>,
     on_updated: Callback<Todo>,
     on_deleted: Callback<Uuid>,
 ) -> impl IntoView {
+    let latest = todo;
+    let todo = latest.get_untracked();
     let (is_editing, set_is_editing) = create_signal(false);
     let (title, set_title) = create_signal(todo.title.clone());
     let (description, set_description) = create_signal(todo.description.clone());
     let (status, set_status) = create_signal(todo.status);
+    let (cancellation_reason, set_cancellation_reason) = create_signal(todo.cancellation_reason.clone());
+    let (priority, set_priority) = create_signal(todo.priority);
+    let (due_at, set_due_at) = create_signal(todo.due_at);
+    let (tags, set_tags) = create_signal(todo.tags.clone());
+    let (assignee_id, set_assignee_id) = create_signal(todo.assignee_id);
+    let (due_input, set_due_input) = create_signal(
+        todo.due_at
+            .map(|due| due.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+    );
+    let (tags_input, set_tags_input) = create_signal(todo.tags.join(", "));
     let (is_updating, set_is_updating) = create_signal(false);
+    let (version, set_version) = create_signal(todo.version);
+    // An update refused because the todo changed meanwhile, kept for "Overwrite"
+    let (conflict, set_conflict) = create_signal(None::<TodoUpdate>);
+    let (field_errors, set_field_errors) = create_signal(Vec::<FieldError>::new());
 
     let todo_id = todo.id;
+    let owner_id = todo.owner_id;
 
-    let handle_status_change = move |_| {
-        let on_updated = on_updated.clone();
-        let new_status = match status.get() {
-            TodoStatus::Pending => TodoStatus::InProgress,
-            TodoStatus::InProgress => TodoStatus::Completed,
-            TodoStatus::Completed => TodoStatus::Pending,
-            TodoStatus::Cancelled => TodoStatus::Pending,
-        };
+    // Shows `updated` in this item
+    let show_todo = move |updated: Todo| {
+        set_title.set(updated.title.clone());
+        set_description.set(updated.description.clone());
+        set_status.set(updated.status);
+        set_cancellation_reason.set(updated.cancellation_reason.clone());
+        set_priority.set(updated.priority);
+        set_due_at.set(updated.due_at);
+        set_due_input.set(
+            updated.due_at
+                .map(|due| due.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        );
+        set_tags.set(updated.tags.clone());
+        set_tags_input.set(updated.tags.join(", "));
+        set_assignee_id.set(updated.assignee_id);
+        set_version.set(updated.version);
+    };
+
+    // Shows a saved or reloaded todo and passes it up to the parent
+    let apply_todo = move |updated: Todo| {
+        show_todo(updated.clone());
+        on_updated.call(updated);
+    };
+
+    // Someone else changed it; an open edit keeps its values and meets the
+    // conflict dialog on save, then shows the change once closed
+    create_effect(move |_| {
+        let newer = latest.with(|todo| todo.version > version.get_untracked());
+        if newer && !is_editing.get() {
+            show_todo(latest.get_untracked());
+        }
+    });
 
+    // Sends `update` against the version this item last saw
+    let send_update = move |update: TodoUpdate| {
         set_is_updating.set(true);
         spawn_local(async move {
-            let update = TodoUpdate {
-                title: None,
-                description: None,
-                status: Some(new_status),
-            };
-
-            match api::update_todo(todo_id, update).await {
+            match api::update_todo(todo_id, version.get_untracked(), update.clone()).await {
                 Ok(updated_todo) => {
-                    set_status.set(updated_todo.status);
-                    on_updated.call(updated_todo);
+                    apply_todo(updated_todo);
+                    set_is_editing.set(false);
+                }
+                Err(e) if e == api::EDIT_CONFLICT => {
+                    set_conflict.set(Some(update));
                 }
                 Err(e) => {
                     log::error!("Failed to update todo: {}", e);
@@ -48,31 +118,67 @@ pub fn TodoItem(
         });
     };
 
-    let handle_save = move |_| {
-        let on_updated = on_updated.clone();
-        set_is_updating.set(true);
+    // Loads the latest version, then either shows it or re-sends the refused update on top of it
+    let resolve_conflict = move |overwrite: bool| {
+        let Some(pending) = conflict.get_untracked() else {
+            return;
+        };
+        set_conflict.set(None);
         spawn_local(async move {
-            let update = TodoUpdate {
-                title: Some(title.get()),
-                description: Some(description.get().unwrap_or_default()),
-                status: None,
-            };
-
-            match api::update_todo(todo_id, update).await {
-                Ok(updated_todo) => {
-                    set_title.set(updated_todo.title.clone());
-                    set_description.set(updated_todo.description.clone());
-                    on_updated.call(updated_todo);
+            match api::fetch_todo(todo_id).await {
+                Ok(latest) if overwrite => {
+                    set_version.set(latest.version);
+                    send_update(pending);
+                }
+                Ok(latest) => {
+                    apply_todo(latest);
                     set_is_editing.set(false);
                 }
                 Err(e) => {
-                    log::error!("Failed to update todo: {}", e);
+                    log::error!("Failed to reload todo: {}", e);
                 }
             }
-            set_is_updating.set(false);
         });
     };
 
+    let handle_status_change = move |new_status: TodoStatus| {
+        // Cancelling is final and needs a reason, so ask for one first
+        let status_reason = if new_status.requires_reason() {
+            match window().prompt_with_message("Why is this todo being cancelled?") {
+                Ok(Some(reason)) if !reason.trim().is_empty() => Some(reason),
+                _ => return,
+            }
+        } else {
+            None
+        };
+
+        send_update(TodoUpdate {
+            status: Some(new_status),
+            status_reason,
+            ..Default::default()
+        });
+    };
+
+    let handle_save = move |_| {
+        let update = TodoUpdate {
+            title: Some(title.get()),
+            // Emptied fields are sent as null so the server clears them
+            description: Patch::from_option(description.get().filter(|d| !d.trim().is_empty())),
+            priority: Some(priority.get()),
+            due_at: Patch::from_option(parse_due_date(&due_input.get())),
+            tags: Some(parse_tags(&tags_input.get())),
+            assignee_id: Patch::from_option(assignee_id.get()),
+            ..Default::default()
+        };
+        match update.validate() {
+            Ok(()) => {
+                set_field_errors.set(Vec::new());
+                send_update(update);
+            }
+            Err(fields) => set_field_errors.set(fields),
+        }
+    };
+
     let handle_delete = move |_| {
         let on_deleted = on_deleted.clone();
         set_is_updating.set(true);
@@ -116,12 +222,46 @@ pub fn TodoItem(
                                 placeholder="Title"
                                 class="w-full px-4 py-2 border-2 border-slate-200 rounded-lg focus:outline-none focus:border-blue-500 transition-colors"
                             />
+                            <FieldErrorText errors=field_errors field="title" label="Title"/>
                             <textarea
                                 prop:value=move || description.get().unwrap_or_default()
                                 on:input=move |ev| set_description.set(Some(event_target_value(&ev)))
                                 placeholder="Description (optional)"
                                 class="w-full px-4 py-2 border-2 border-slate-200 rounded-lg focus:outline-none focus:border-blue-500 transition-colors min-h-[80px] resize-y"
                             />
+                            <FieldErrorText errors=field_errors field="description" label="Description"/>
+                            <div class="grid grid-cols-1 md:grid-cols-4 gap-2">
+                                <select
+                                    on:change=move |ev| set_priority.set(parse_priority(&event_target_value(&ev)))
+                                    prop:value=move || priority.get().as_str()
+                                    class="px-4 py-2 border-2 border-slate-200 rounded-lg bg-white focus:outline-none focus:border-blue-500"
+                                >
+                                    <option value="low">"Low priority"</option>
+                                    <option value="medium">"Medium priority"</option>
+                                    <option value="high">"High priority"</option>
+                                    <option value="urgent">"Urgent"</option>
+                                </select>
+                                <input
+                                    type="date"
+                                    prop:value=due_input
+                                    on:input=move |ev| set_due_input.set(event_target_value(&ev))
+                                    title="Due date"
+                                    class="px-4 py-2 border-2 border-slate-200 rounded-lg focus:outline-none focus:border-blue-500"
+                                />
+                                <input
+                                    type="text"
+                                    prop:value=tags_input
+                                    on:input=move |ev| set_tags_input.set(event_target_value(&ev))
+                                    placeholder="Tags, comma separated"
+                                    class="px-4 py-2 border-2 border-slate-200 rounded-lg focus:outline-none focus:border-blue-500"
+                                />
+                                <AssigneeSelect
+                                    value=assignee_id
+                                    on_change=Callback::new(move |id| set_assignee_id.set(id))
+                                    class="px-4 py-2 border-2 border-slate-200 rounded-lg bg-white focus:outline-none focus:border-blue-500"
+                                />
+                            </div>
+                            <FieldErrorText errors=field_errors field="tags" label="Tags:"/>
                             <div class="flex gap-2 flex-wrap">
                                 <button 
                                     on:click=handle_save 
@@ -144,7 +284,9 @@ pub fn TodoItem(
                     view! {
                         <div class="space-y-4">
                             <div>
-                                <h3 class="text-xl font-semibold text-slate-800 mb-2">{title}</h3>
+                                <h3 class="text-xl font-semibold text-slate-800 mb-2">
+                                    <A href=format!("/todos/{}", todo_id) class="hover:underline">{title}</A>
+                                </h3>
                                 {move || {
                                     description
                                         .get()
@@ -152,20 +294,74 @@ pub fn TodoItem(
                                             view! { <p class="text-slate-600">{desc}</p> }
                                         })
                                 }}
+                                {move || {
+                                    cancellation_reason
+                                        .get()
+                                        .map(|reason| {
+                                            view! { <p class="text-sm text-red-700 mt-2">{format!("Cancelled: {}", reason)}</p> }
+                                        })
+                                }}
                             </div>
-                            <div class="flex items-center gap-3">
+                            <div class="flex flex-wrap items-center gap-3">
                                 <span class=move || format!("inline-block px-3 py-1 rounded-full text-sm font-semibold {}", status_badge_color())>
                                     {move || format!("{:?}", status.get())}
                                 </span>
+                                {move || {
+                                    let current = priority.get();
+                                    view! {
+                                        <Badge variant=priority_variant(current) pill=true>
+                                            {current.as_str()}
+                                        </Badge>
+                                    }
+                                }}
+                                {move || {
+                                    due_at
+                                        .get()
+                                        .map(|due| {
+                                            let open = matches!(status.get(), TodoStatus::Pending | TodoStatus::InProgress);
+                                            let overdue = open && due < chrono::Utc::now();
+                                            let variant = if overdue { BadgeVariant::Danger } else { BadgeVariant::Secondary };
+                                            view! {
+                                                <Badge variant=variant pill=true>
+                                                    {format!("Due {}", due.format("%Y-%m-%d"))}
+                                                </Badge>
+                                            }
+                                        })
+                                }}
+                                {move || {
+                                    tags.get()
+                                        .into_iter()
+                                        .map(|tag| {
+                                            view! {
+                                                <Badge variant=BadgeVariant::Primary size=BadgeSize::Sm>
+                                                    {format!("#{}", tag)}
+                                                </Badge>
+                                            }
+                                        })
+                                        .collect_view()
+                                }}
+                                {move || view! { <TodoPeople owner_id=owner_id assignee_id=assignee_id.get()/> }}
                             </div>
                             <div class="flex gap-2 flex-wrap">
-                                <button
-                                    on:click=handle_status_change
-                                    disabled=is_updating
-                                    class="px-4 py-2 bg-blue-600 text-white rounded-lg text-sm font-semibold hover:bg-blue-700 transition-colors disabled:opacity-60 disabled:cursor-not-allowed"
-                                >
-                                    "Toggle Status"
-                                </button>
+                                // Only offer the moves the status state machine allows
+                                {move || {
+                                    status
+                                        .get()
+                                        .allowed_transitions()
+                                        .iter()
+                                        .map(|&next| {
+                                            view! {
+                                                <button
+                                                    on:click=move |_| handle_status_change(next)
+                                                    disabled=is_updating
+                                                    class="px-4 py-2 bg-blue-600 text-white rounded-lg text-sm font-semibold hover:bg-blue-700 transition-colors disabled:opacity-60 disabled:cursor-not-allowed"
+                                                >
+                                                    {status_action_label(next)}
+                                                </button>
+                                            }
+                                        })
+                                        .collect_view()
+                                }}
                                 <button 
                                     on:click=move |_| set_is_editing.set(true)
                                     class="px-4 py-2 bg-slate-600 text-white rounded-lg text-sm font-semibold hover:bg-slate-700 transition-colors"
@@ -187,6 +383,13 @@ pub fn TodoItem(
             }}
 
         </div>
+        <ConflictDialog
+            open=Signal::derive(move || conflict.with(Option::is_some))
+            subject="todo"
+            on_reload=Callback::new(move |_| resolve_conflict(false))
+            on_overwrite=Callback::new(move |_| resolve_conflict(true))
+            on_close=Callback::new(move |_| set_conflict.set(None))
+        />
     }
 }
 
@@ -1,23 +1,226 @@
 use leptos::*;
-use platform_core::Todo;
+use platform_core::{ChangeAction, ChangeEntity, ChangeEvent, Todo, TodoListQuery, MAX_PAGE_LIMIT};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
 
 use crate::api;
+use crate::auth::AuthContext;
 use crate::components::todo_form::TodoForm;
 use crate::components::todo_item::TodoItem;
+use crate::components::ui::{Progress, ProgressSize, ProgressVariant};
+
+/// A todo on screen, so live changes can update it in place
+#[derive(Clone, Copy)]
+struct ShownTodo {
+    todo: RwSignal<Todo>,
+    /// Refetches the todo's roll-up and, if expanded, its subtasks
+    reload: Callback<()>,
+}
+
+/// Every [`TodoNode`] on screen, by todo id
+#[derive(Clone, Copy)]
+struct ShownTodos(StoredValue<HashMap<Uuid, ShownTodo>>);
+
+impl ShownTodos {
+    fn get(&self, id: Uuid) -> Option<ShownTodo> {
+        self.0.with_value(|shown| shown.get(&id).copied())
+    }
+}
+
+/// A todo with its roll-up progress and, once expanded, its subtasks
+///
+/// Returns `View` rather than `impl IntoView` because it renders itself recursively.
+#[component]
+fn TodoNode(todo: Todo, on_updated: Callback<Todo>, on_deleted: Callback<Uuid>) -> View {
+    let todo_id = todo.id;
+    let current = create_rw_signal(todo);
+    let (children, set_children) = create_signal(Vec::<Todo>::new());
+    let (expanded, set_expanded) = create_signal(false);
+    let (adding, set_adding) = create_signal(false);
+
+    let load_children = move || {
+        spawn_local(async move {
+            match api::fetch_todo_children(todo_id).await {
+                Ok(fetched) => set_children.set(fetched),
+                Err(e) => log::error!("Failed to load subtasks: {}", e),
+            }
+        });
+    };
+
+    let toggle_expanded = move |_| {
+        if !expanded.get() {
+            load_children();
+        }
+        set_expanded.update(|open| *open = !*open);
+    };
+
+    // A subtask changed, so refetch this todo's roll-up and pass it up the tree
+    let refresh = move || {
+        spawn_local(async move {
+            match api::fetch_todo(todo_id).await {
+                Ok(fresh) => {
+                    current.set(fresh.clone());
+                    on_updated.call(fresh);
+                }
+                Err(e) => log::error!("Failed to refresh todo: {}", e),
+            }
+        });
+    };
+
+    if let Some(ShownTodos(shown)) = use_context::<ShownTodos>() {
+        let reload = Callback::new(move |_| {
+            refresh();
+            if expanded.get_untracked() {
+                load_children();
+            }
+        });
+        shown.update_value(|shown| {
+            shown.insert(todo_id, ShownTodo { todo: current, reload });
+        });
+        on_cleanup(move || {
+            shown.update_value(|shown| {
+                shown.remove(&todo_id);
+            })
+        });
+    }
+
+    let on_self_updated = Callback::new(move |updated: Todo| {
+        current.set(updated.clone());
+        on_updated.call(updated);
+    });
+
+    let on_child_updated = Callback::new(move |updated: Todo| {
+        set_children.update(|children| {
+            if let Some(child) = children.iter_mut().find(|t| t.id == updated.id) {
+                *child = updated;
+            }
+        });
+        refresh();
+    });
+
+    let on_child_deleted = Callback::new(move |id: Uuid| {
+        set_children.update(|children| children.retain(|t| t.id != id));
+        refresh();
+    });
+
+    let on_child_created = Callback::new(move |child: Todo| {
+        set_children.update(|children| children.push(child));
+        set_adding.set(false);
+        set_expanded.set(true);
+        refresh();
+    });
+
+    view! {
+        <div class="space-y-2">
+            <TodoItem todo=current on_updated=on_self_updated on_deleted=on_deleted/>
+            <div class="flex items-center gap-3 px-2">
+                {move || {
+                    let todo = current.get();
+                    todo.subtask_progress().map(|progress| {
+                        view! {
+                            <Progress
+                                value=Signal::derive(move || progress * 100.0)
+                                variant=ProgressVariant::Success
+                                size=ProgressSize::Sm
+                                class="flex-1".to_string()
+                            />
+                            <span class="text-xs text-slate-500 whitespace-nowrap">
+                                {format!("{}/{} subtasks", todo.subtask_completed, todo.subtask_total)}
+                            </span>
+                        }
+                    })
+                }}
+                {move || {
+                    (expanded.get() || current.with(|todo| todo.subtask_total > 0)).then(|| {
+                        view! {
+                            <button
+                                on:click=toggle_expanded
+                                class="text-xs font-semibold text-blue-600 hover:text-blue-800 whitespace-nowrap"
+                            >
+                                {move || if expanded.get() { "Hide subtasks" } else { "Show subtasks" }}
+                            </button>
+                        }
+                    })
+                }}
+                <button
+                    on:click=move |_| set_adding.update(|open| *open = !*open)
+                    class="text-xs font-semibold text-slate-600 hover:text-slate-900 whitespace-nowrap"
+                >
+                    "+ Subtask"
+                </button>
+            </div>
+            {move || {
+                adding.get().then(|| {
+                    view! {
+                        <div class="ml-6">
+                            <TodoForm on_created=on_child_created parent_id=todo_id/>
+                        </div>
+                    }
+                })
+            }}
+            {move || {
+                expanded.get().then(|| {
+                    view! {
+                        <div class="ml-6 pl-4 border-l-2 border-slate-200 space-y-4">
+                            <For
+                                each=move || children.get()
+                                key=|child| child.id
+                                children=move |child| {
+                                    view! {
+                                        <TodoNode
+                                            todo=child
+                                            on_updated=on_child_updated
+                                            on_deleted=on_child_deleted
+                                        />
+                                    }
+                                }
+                            />
+                        </div>
+                    }
+                })
+            }}
+        </div>
+    }
+    .into_view()
+}
+
+/// Whether `todo` shows under "My todos" for `user_id`, as the server decides
+fn is_mine(todo: &Todo, user_id: Uuid) -> bool {
+    match todo.assignee_id {
+        Some(assignee) => assignee == user_id,
+        None => todo.owner_id == Some(user_id),
+    }
+}
+
+/// Top-level todos, optionally only the signed-in user's, in pages as large as allowed
+fn list_query(mine: bool) -> TodoListQuery {
+    TodoListQuery {
+        limit: Some(MAX_PAGE_LIMIT),
+        top_level: true,
+        mine,
+        ..Default::default()
+    }
+}
 
 #[component]
 pub fn TodoList() -> impl IntoView {
     let (todos, set_todos) = create_signal(Vec::<Todo>::new());
     let (loading, set_loading) = create_signal(true);
     let (error, set_error) = create_signal(None::<String>);
+    // Only todos assigned to the signed-in user, or created by them and unassigned
+    let (mine, set_mine) = create_signal(false);
+    let signed_in_user = expect_context::<AuthContext>().user;
+    let shown = ShownTodos(store_value(HashMap::new()));
+    provide_context(shown);
 
-    // Load todos on mount
+    // Load todos on mount and whenever the filter changes
     create_effect(move |_| {
+        let query = list_query(mine.get());
         spawn_local(async move {
             set_loading.set(true);
-            match api::fetch_todos().await {
-                Ok(fetched_todos) => {
-                    set_todos.set(fetched_todos);
+            match api::fetch_all_todos(&query).await {
+                Ok(fetched) => {
+                    set_todos.set(fetched);
                     set_error.set(None);
                 }
                 Err(e) => {
@@ -28,6 +231,81 @@ pub fn TodoList() -> impl IntoView {
         });
     });
 
+    // Fetches one todo again and shows it where it now belongs
+    let refetch = move |id: Uuid| {
+        let user_id = signed_in_user.with_untracked(|user| user.as_ref().map(|user| user.id));
+        spawn_local(async move {
+            let fresh = match api::fetch_todo(id).await {
+                Ok(fresh) => fresh,
+                Err(e) => {
+                    // Usually deleted since, which has its own event
+                    log::warn!("Failed to reload todo {}: {}", id, e);
+                    return;
+                }
+            };
+            if let Some(node) = shown.get(id) {
+                node.todo.set(fresh.clone());
+            }
+            let listed = fresh.parent_id.is_none()
+                && (!mine.get_untracked() || user_id.is_some_and(|user_id| is_mine(&fresh, user_id)));
+            set_todos.update(|todos| match todos.iter().position(|t| t.id == id) {
+                Some(index) if listed => todos[index] = fresh,
+                Some(index) => {
+                    todos.remove(index);
+                }
+                None if listed => todos.insert(0, fresh),
+                None => {}
+            });
+        });
+    };
+
+    // Updates the todos someone else changed, and the parents whose progress moved with them
+    let apply_changes = move |events: Vec<ChangeEvent>| {
+        let latest: HashMap<Uuid, ChangeEvent> =
+            events.into_iter().map(|event| (event.id, event)).collect();
+        let mut parents = HashSet::new();
+        for event in latest.into_values() {
+            let node = shown.get(event.id);
+            // Its old parent as well, in case it moved
+            parents.extend(node.and_then(|node| node.todo.with_untracked(|todo| todo.parent_id)));
+            parents.extend(event.parent_id);
+
+            if event.action == ChangeAction::Deleted {
+                set_todos.update(|todos| todos.retain(|t| t.id != event.id));
+                continue;
+            }
+            // Our own edits have already been shown at this version
+            let stale = node.is_some_and(|node| node.todo.with_untracked(|todo| todo.version < event.version));
+            let listed = todos.with_untracked(|todos| todos.iter().any(|t| t.id == event.id));
+            if stale || listed != event.parent_id.is_none() {
+                refetch(event.id);
+            }
+        }
+        for parent in parents {
+            if let Some(node) = shown.get(parent) {
+                node.reload.call(());
+            }
+        }
+    };
+
+    // Without the loading state, so what is on screen stays put
+    let feed = api::watch_changes(&[ChangeEntity::Todo], move |changes| match changes {
+        api::Changes::Records(events) => apply_changes(events),
+        api::Changes::Unknown => {
+            let query = list_query(mine.get_untracked());
+            spawn_local(async move {
+                match api::fetch_all_todos(&query).await {
+                    Ok(fetched) => set_todos.set(fetched),
+                    Err(e) => log::error!("Failed to reload todos: {}", e),
+                }
+            });
+            for node in shown.0.with_value(|shown| shown.values().copied().collect::<Vec<_>>()) {
+                node.reload.call(());
+            }
+        }
+    });
+    on_cleanup(move || drop(feed));
+
     let on_todo_created = Callback::new(move |new_todo: Todo| {
         set_todos.update(|todos| {
             todos.insert(0, new_todo);
@@ -52,6 +330,28 @@ pub fn TodoList() -> impl IntoView {
         <div class="max-w-3xl mx-auto">
             <TodoForm on_created=on_todo_created/>
 
+            <div class="flex gap-2 mb-4">
+                {[(false, "All todos"), (true, "My todos")]
+                    .into_iter()
+                    .map(|(value, label)| {
+                        view! {
+                            <button
+                                on:click=move |_| set_mine.set(value)
+                                class=move || {
+                                    if mine.get() == value {
+                                        "px-4 py-2 rounded-lg text-sm font-semibold bg-slate-800 text-white"
+                                    } else {
+                                        "px-4 py-2 rounded-lg text-sm font-semibold bg-white text-slate-700 shadow-sm hover:bg-slate-100 transition-colors"
+                                    }
+                                }
+                            >
+                                {label}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+
             {move || {
                 if loading.get() {
                     view! { 
@@ -70,7 +370,7 @@ pub fn TodoList() -> impl IntoView {
                     if todos_list.is_empty() {
                         view! { 
                             <div class="text-center p-8 bg-white rounded-xl shadow-md text-slate-600">
-                                "No todos yet. Create one above!"
+                                {move || if mine.get() { "Nothing is assigned to you." } else { "No todos yet. Create one above!" }}
                             </div> 
                         }
                             .into_view()
@@ -82,7 +382,7 @@ pub fn TodoList() -> impl IntoView {
                                     key=|todo| todo.id
                                     children=move |todo| {
                                         view! {
-                                            <TodoItem
+                                            <TodoNode
                                                 todo=todo
                                                 on_updated=on_todo_updated
                                                 on_deleted=on_todo_deleted
@@ -0,0 +1,141 @@
+use leptos::*;
+use platform_core::{highlight_segments, TodoSearchHit, TodoSearchQuery};
+
+use crate::api;
+
+/// Renders a search snippet, wrapping matched terms in `<mark>` elements
+fn render_snippet(snippet: &str) -> View {
+    highlight_segments(snippet)
+        .into_iter()
+        .map(|(text, is_match)| {
+            let text = text.to_string();
+            if is_match {
+                view! { <mark class="bg-yellow-200 rounded px-0.5">{text}</mark> }.into_view()
+            } else {
+                text.into_view()
+            }
+        })
+        .collect_view()
+}
+
+#[component]
+pub fn TodoSearch() -> impl IntoView {
+    let (query, set_query) = create_signal(String::new());
+    let (results, set_results) = create_signal(None::<Vec<TodoSearchHit>>);
+    let (is_searching, set_is_searching) = create_signal(false);
+    let (error, set_error) = create_signal(None::<String>);
+
+    let handle_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+
+        let q = query.get();
+        if q.trim().is_empty() {
+            set_results.set(None);
+            set_error.set(None);
+            return;
+        }
+
+        set_is_searching.set(true);
+        set_error.set(None);
+
+        spawn_local(async move {
+            let search = TodoSearchQuery { q, limit: None };
+            match api::search_todos(&search).await {
+                Ok(hits) => {
+                    set_results.set(Some(hits));
+                }
+                Err(e) => {
+                    set_error.set(Some(e));
+                }
+            }
+            set_is_searching.set(false);
+        });
+    };
+
+    let handle_clear = move |_| {
+        set_query.set(String::new());
+        set_results.set(None);
+        set_error.set(None);
+    };
+
+    view! {
+        <div class="max-w-3xl mx-auto bg-white p-6 rounded-xl shadow-md mb-8 text-left">
+            <form on:submit=handle_submit class="flex gap-2">
+                <input
+                    type="search"
+                    prop:value=query
+                    on:input=move |ev| set_query.set(event_target_value(&ev))
+                    placeholder="Search todos"
+                    class="flex-1 px-4 py-3 border-2 border-slate-200 rounded-lg focus:outline-none focus:border-primary transition-colors"
+                />
+                <button
+                    type="submit"
+                    disabled=is_searching
+                    style="background-color: var(--color-primary);"
+                    class="px-6 py-3 text-white rounded-lg font-semibold hover:opacity-90 transition-all disabled:opacity-60 disabled:cursor-not-allowed"
+                >
+                    {move || if is_searching.get() { "Searching..." } else { "Search" }}
+                </button>
+            </form>
+
+            {move || {
+                error
+                    .get()
+                    .map(|err| {
+                        view! { <div class="text-red-600 text-sm mt-4">{err}</div> }
+                    })
+            }}
+
+            {move || {
+                results
+                    .get()
+                    .map(|hits| {
+                        let count = hits.len();
+                        view! {
+                            <div class="mt-6">
+                                <div class="flex items-center justify-between mb-3">
+                                    <span class="text-sm text-slate-500">
+                                        {format!("{} result{}", count, if count == 1 { "" } else { "s" })}
+                                    </span>
+                                    <button
+                                        on:click=handle_clear
+                                        class="text-sm text-slate-600 hover:text-slate-900"
+                                    >
+                                        "Clear"
+                                    </button>
+                                </div>
+                                <ul class="divide-y divide-slate-100">
+                                    {hits
+                                        .into_iter()
+                                        .map(|hit| {
+                                            view! {
+                                                <li class="py-3">
+                                                    <div class="flex items-baseline justify-between gap-4">
+                                                        <h4 class="font-semibold text-slate-800">
+                                                            {render_snippet(&hit.title_snippet)}
+                                                        </h4>
+                                                        <span class="text-xs text-slate-400" title="Relevance">
+                                                            {format!("{:.3}", hit.rank)}
+                                                        </span>
+                                                    </div>
+                                                    {hit
+                                                        .description_snippet
+                                                        .map(|snippet| {
+                                                            view! {
+                                                                <p class="text-sm text-slate-600 mt-1">
+                                                                    {render_snippet(&snippet)}
+                                                                </p>
+                                                            }
+                                                        })}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                            </div>
+                        }
+                    })
+            }}
+        </div>
+    }
+}
@@ -9,6 +9,15 @@ pub enum AvatarSize {
     Xl,
 }
 
+/// Up to two initials for a display name, e.g. "Ada Lovelace" -> "AL"
+pub fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .collect::<String>()
+        .to_uppercase()
+}
+
 #[component]
 pub fn Avatar(
     #[prop(optional, default = String::new())] src: String,
@@ -56,15 +65,18 @@ pub fn Avatar(
 #[component]
 pub fn AvatarGroup(
     children: Children,
-    #[prop(optional, default = 4)] max: usize,
+    /// Avatars left out of `children`, shown as a trailing "+N"
+    #[prop(optional, default = 0)] hidden: usize,
     #[prop(optional, default = String::new())] class: String,
 ) -> impl IntoView {
     view! {
         <div class=format!("flex -space-x-2 {}", class)>
             {children()}
-            <div class="flex items-center justify-center w-10 h-10 rounded-full bg-slate-300 dark:bg-slate-600 text-slate-700 dark:text-slate-300 font-semibold text-sm border-2 border-white dark:border-slate-800">
-                {format!("+{}", max)}
-            </div>
+            {(hidden > 0).then(|| view! {
+                <div class="flex items-center justify-center w-10 h-10 rounded-full bg-slate-300 dark:bg-slate-600 text-slate-700 dark:text-slate-300 font-semibold text-sm border-2 border-white dark:border-slate-800">
+                    {format!("+{}", hidden)}
+                </div>
+            })}
         </div>
     }
 }
@@ -1,4 +1,5 @@
 use leptos::*;
+use std::collections::HashSet;
 use std::rc::Rc;
 
 /// Column definition for DataTable
@@ -54,6 +55,9 @@ pub fn DataTable<T, K>(
     #[prop(optional, default = false)] loading: bool,
     #[prop(optional, default = "No data to display".to_string())] empty_message: String,
     #[prop(optional, default = String::new())] class: String,
+    /// Enables multi-select mode; holds the keys of the selected rows
+    #[prop(optional)]
+    selection: Option<RwSignal<HashSet<String>>>,
 ) -> impl IntoView
 where
     T: Clone + 'static,
@@ -88,6 +92,36 @@ where
                         <table class=combined_classes.clone()>
                             <thead class="bg-slate-100 border-b border-slate-200">
                                 <tr>
+                                    {selection.map(|selected| {
+                                        let all_selected = move || {
+                                            let rows = data.get();
+                                            !rows.is_empty()
+                                                && selected.with(|keys| rows.iter().all(|row| keys.contains(&key_fn(row))))
+                                        };
+                                        view! {
+                                            <th class="px-4 py-3 w-10">
+                                                <input
+                                                    type="checkbox"
+                                                    prop:checked=all_selected
+                                                    on:change=move |_| {
+                                                        let select_all = !all_selected();
+                                                        let keys: Vec<String> = data.get().iter().map(key_fn).collect();
+                                                        selected.update(|set| {
+                                                            for key in keys {
+                                                                if select_all {
+                                                                    set.insert(key);
+                                                                } else {
+                                                                    set.remove(&key);
+                                                                }
+                                                            }
+                                                        });
+                                                    }
+                                                    aria-label="Select all rows"
+                                                    class="cursor-pointer"
+                                                />
+                                            </th>
+                                        }
+                                    })}
                                     {columns
                                         .iter()
                                         .map(|col| {
@@ -114,8 +148,34 @@ where
                                     each=move || data.get()
                                     key=key_fn
                                     children=move |item: T| {
+                                        let row_key = key_fn(&item);
                                         view! {
                                             <tr class="hover:bg-slate-50 transition-colors">
+                                                {selection.map(|selected| {
+                                                    let key = row_key.clone();
+                                                    let is_selected = move || selected.with(|keys| keys.contains(&key));
+                                                    let key = row_key.clone();
+                                                    view! {
+                                                        <td class="px-4 py-4 w-10">
+                                                            <input
+                                                                type="checkbox"
+                                                                prop:checked=is_selected
+                                                                on:change=move |ev| {
+                                                                    let checked = event_target_checked(&ev);
+                                                                    selected.update(|set| {
+                                                                        if checked {
+                                                                            set.insert(key.clone());
+                                                                        } else {
+                                                                            set.remove(&key);
+                                                                        }
+                                                                    });
+                                                                }
+                                                                aria-label="Select row"
+                                                                class="cursor-pointer"
+                                                            />
+                                                        </td>
+                                                    }
+                                                })}
                                                 {columns
                                                     .iter()
                                                     .map(|col| {
@@ -40,7 +40,7 @@ pub use modal::{Modal, ModalSize};
 pub use spinner::{Spinner, SpinnerOverlay, SpinnerSize};
 pub use progress::{Progress, ProgressVariant, ProgressSize};
 pub use badge::{Badge, BadgeVariant, BadgeSize};
-pub use avatar::{Avatar, AvatarGroup, AvatarSize};
+pub use avatar::{initials, Avatar, AvatarGroup, AvatarSize};
 pub use divider::{Divider, DividerOrientation};
 pub use dropdown::{Dropdown, DropdownItem, DropdownDivider};
 pub use breadcrumbs::{Breadcrumbs, BreadcrumbItem};
@@ -1,6 +1,9 @@
 use leptos::*;
 use leptos_router::*;
-use super::ThemeToggle;
+use platform_core::Permission;
+use super::{initials, Avatar, AvatarSize, ThemeToggle};
+use crate::api;
+use crate::auth::{self, AuthContext};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct NavItem {
@@ -13,12 +16,45 @@ pub struct NavItem {
 pub struct NavGroup {
     pub title: String,
     pub items: Vec<NavItem>,
+    /// Hidden unless the signed-in user has this permission
+    pub permission: Option<Permission>,
 }
 
 #[component]
 pub fn SideNav(
     groups: Vec<NavGroup>,
 ) -> impl IntoView {
+    let auth_ctx = use_context::<AuthContext>();
+    let user = auth_ctx.as_ref().map(|ctx| ctx.user);
+    let navigate = use_navigate();
+
+    let visible_groups = move || {
+        let current = user.and_then(|user| user.get());
+        groups
+            .iter()
+            .filter(|group| match group.permission {
+                None => true,
+                Some(permission) => current.as_ref().is_some_and(|user| user.can(permission)),
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let sign_out = move |_| {
+        let navigate = navigate.clone();
+        let auth_ctx = auth_ctx.clone();
+        spawn_local(async move {
+            if let Err(e) = api::logout().await {
+                log::error!("Failed to sign out: {}", e);
+            }
+            match &auth_ctx {
+                Some(auth_ctx) => auth_ctx.sign_out(),
+                None => auth::clear_token(),
+            }
+            navigate("/login", NavigateOptions::default());
+        });
+    };
+
     view! {
         <nav class="w-64 bg-slate-800 dark:bg-slate-900 text-white min-h-screen p-4 flex flex-col border-r border-slate-700">
             <div class="mb-8">
@@ -32,7 +68,7 @@ pub fn SideNav(
             
             <div class="flex-1 space-y-6">
                 <For
-                    each=move || groups.clone()
+                    each=visible_groups
                     key=|group| group.title.clone()
                     children=move |group| {
                         view! {
@@ -60,6 +96,24 @@ pub fn SideNav(
                     }
                 />
             </div>
+
+            {move || user.and_then(|user| user.get()).map(|current| {
+                view! {
+                    <div class="mt-6 pt-4 border-t border-slate-700 flex items-center gap-3">
+                        <Avatar initials=initials(&current.display_name) size=AvatarSize::Sm/>
+                        <div class="flex-1 min-w-0">
+                            <p class="text-sm font-medium truncate">{current.display_name.clone()}</p>
+                            <p class="text-xs text-slate-400 truncate">{current.email.clone()}</p>
+                        </div>
+                    </div>
+                }
+            })}
+            <button
+                on:click=sign_out
+                class="mt-3 px-3 py-2 rounded-lg text-sm font-medium text-left text-slate-300 hover:bg-slate-700 transition-colors"
+            >
+                "Sign out"
+            </button>
         </nav>
     }
 }
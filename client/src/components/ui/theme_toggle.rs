@@ -1,7 +1,7 @@
 use leptos::*;
+use crate::auth::AuthContext;
 use crate::theme::ThemeContext;
-use crate::api;
-use platform_core::ComponentSettingUpdate;
+use platform_core::Permission;
 
 #[component]
 pub fn ThemeToggle() -> impl IntoView {
@@ -11,34 +11,26 @@ pub fn ThemeToggle() -> impl IntoView {
     let current_mode = create_memo(move |_| theme_for_memo.get_theme_mode());
     let is_dark = create_memo(move |_| current_mode.get() == "dark");
 
+    // Only users who may change settings save the mode for everyone
+    let user = use_context::<AuthContext>().map(|auth| auth.user);
+    let can_save = move || {
+        user.and_then(|user| user.get_untracked())
+            .is_some_and(|user| user.can(Permission::ManageSettings))
+    };
+
     let toggle_theme = move |_| {
         let new_mode = if is_dark.get() { "light" } else { "dark" };
         let theme = theme.clone();
+
+        if !can_save() {
+            theme.set("theme_mode".to_string(), new_mode.to_string());
+            theme.apply_to_document();
+            return;
+        }
         
         spawn_local(async move {
-            // Find the theme_mode setting ID
-            match api::fetch_settings().await {
-                Ok(settings) => {
-                    if let Some(setting) = settings.iter().find(|s| s.key == "theme_mode") {
-                        let update = ComponentSettingUpdate {
-                            value: Some(new_mode.to_string()),
-                            description: None,
-                        };
-                        
-                        match api::update_setting(setting.id, update).await {
-                            Ok(_) => {
-                                theme.set("theme_mode".to_string(), new_mode.to_string());
-                                theme.apply_to_document();
-                            }
-                            Err(e) => {
-                                log::error!("Failed to update theme mode: {}", e);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::error!("Failed to fetch settings: {}", e);
-                }
+            if let Err(e) = theme.save_to_active_theme("theme_mode", new_mode.to_string()).await {
+                log::error!("Failed to update theme mode: {}", e);
             }
         });
     };
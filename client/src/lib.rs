@@ -1,4 +1,5 @@
 mod api;
+mod auth;
 mod components;
 mod pages;
 mod theme;
@@ -9,7 +10,9 @@ use leptos_router::*;
 use wasm_bindgen::prelude::*;
 
 use components::ui::sidenav::{SideNav, NavGroup, NavItem};
-use pages::{HomePage, AdminTodosPage, SettingsThemePage, ComponentShowcasePage};
+use auth::AuthContext;
+use platform_core::{ChangeEntity, Permission};
+use pages::{HomePage, AdminTodosPage, SettingsThemePage, SettingsTokensPage, ComponentShowcasePage, TodoDetailPage, TrashPage, LoginPage};
 use theme::ThemeContext;
 
 #[component]
@@ -23,19 +26,83 @@ pub fn App() -> impl IntoView {
     // Apply cached theme immediately to prevent flash
     theme.apply_to_document();
 
-    // Then fetch fresh settings from API in background
+    let auth_ctx = AuthContext::new();
+    provide_context(auth_ctx.clone());
+
+    // Then fetch fresh settings from API in background, again after each sign-in
     let theme_clone = theme.clone();
+    let signed_in_ctx = auth_ctx.clone();
     create_effect(move |_| {
+        if !signed_in_ctx.is_signed_in() {
+            return;
+        }
+        // Follow theme activations and setting edits made in other browsers
+        let live_theme = theme_clone.clone();
+        let feed = api::watch_changes(&[ChangeEntity::Theme, ChangeEntity::ThemeSetting], move |_| {
+            let theme = live_theme.clone();
+            spawn_local(async move {
+                if let Err(e) = theme.load_active_theme().await {
+                    log::error!("Failed to reload the active theme: {}", e);
+                }
+            });
+        });
+        on_cleanup(move || drop(feed));
+
         let theme = theme_clone.clone();
         spawn_local(async move {
-            match api::fetch_settings().await {
-                Ok(settings) => {
-                    theme.load_from_api(settings);
-                    theme.apply_to_document();
-                }
-                Err(e) => {
-                    log::error!("Failed to load theme settings: {}", e);
+            if let Err(e) = theme.load_active_theme().await {
+                log::error!("Failed to load the active theme: {}", e);
+            }
+        });
+    });
+
+    view! {
+        <Title text="Atom Platform - Admin"/>
+        <Router>
+            <Routes>
+                <Route path="/login" view=LoginPage/>
+                <Route path="" view=AppShell>
+                    <Route path="" view=HomePage/>
+                    <Route path="/todos/:id" view=TodoDetailPage/>
+                    <Route path="/admin/todos" view=AdminTodosPage/>
+                    <Route path="/admin/trash" view=TrashPage/>
+                    <Route path="/settings/theme" view=SettingsThemePage/>
+                    <Route path="/settings/tokens" view=SettingsTokensPage/>
+                    <Route path="/showcase" view=ComponentShowcasePage/>
+                </Route>
+            </Routes>
+        </Router>
+    }
+}
+
+/// Side navigation around every page except the login page
+#[component]
+fn AppShell() -> impl IntoView {
+    let auth_ctx = expect_context::<AuthContext>();
+
+    let load_ctx = auth_ctx.clone();
+    create_effect(move |_| {
+        if !load_ctx.is_signed_in() {
+            return;
+        }
+
+        // A reload keeps the token but not the user, so look it up again
+        if load_ctx.user.get_untracked().is_none() {
+            let user = load_ctx.user;
+            spawn_local(async move {
+                match api::fetch_current_user().await {
+                    Ok(current) => user.set(Some(current)),
+                    Err(e) => log::error!("Failed to load the current user: {}", e),
                 }
+            });
+        }
+
+        // Names and initials for owner and assignee avatars
+        let users = load_ctx.users;
+        spawn_local(async move {
+            match api::fetch_assignees().await {
+                Ok(fetched) => users.set(fetched),
+                Err(e) => log::error!("Failed to load users: {}", e),
             }
         });
     });
@@ -49,12 +116,24 @@ pub fn App() -> impl IntoView {
                     path: "/".to_string(),
                     icon: None,
                 },
+            ],
+            permission: None,
+        },
+        NavGroup {
+            title: "Admin".to_string(),
+            items: vec![
                 NavItem {
                     label: "Admin Todos".to_string(),
                     path: "/admin/todos".to_string(),
                     icon: None,
                 },
+                NavItem {
+                    label: "Trash".to_string(),
+                    path: "/admin/trash".to_string(),
+                    icon: None,
+                },
             ],
+            permission: Some(Permission::ManageTodos),
         },
         NavGroup {
             title: "Settings".to_string(),
@@ -65,6 +144,18 @@ pub fn App() -> impl IntoView {
                     icon: None,
                 },
             ],
+            permission: Some(Permission::ManageSettings),
+        },
+        NavGroup {
+            title: "Account".to_string(),
+            items: vec![
+                NavItem {
+                    label: "API Tokens".to_string(),
+                    path: "/settings/tokens".to_string(),
+                    icon: None,
+                },
+            ],
+            permission: None,
         },
         NavGroup {
             title: "Developer".to_string(),
@@ -75,24 +166,23 @@ pub fn App() -> impl IntoView {
                     icon: None,
                 },
             ],
+            permission: None,
         },
     ];
 
+    // Signing out anywhere sends the user to the login page
     view! {
-        <Title text="Atom Platform - Admin"/>
-        <Router>
+        <Show
+            when=move || auth_ctx.is_signed_in()
+            fallback=|| view! { <Redirect path="/login"/> }
+        >
             <div class="flex min-h-screen">
-                <SideNav groups=nav_groups/>
+                <SideNav groups=nav_groups.clone()/>
                 <main class="flex-1 p-8 bg-slate-50">
-                    <Routes>
-                        <Route path="" view=HomePage/>
-                        <Route path="/admin/todos" view=AdminTodosPage/>
-                        <Route path="/settings/theme" view=SettingsThemePage/>
-                        <Route path="/showcase" view=ComponentShowcasePage/>
-                    </Routes>
+                    <Outlet/>
                 </main>
             </div>
-        </Router>
+        </Show>
     }
 }
 
@@ -1,11 +1,19 @@
 use leptos::*;
-use platform_core::{Todo, TodoStatus};
+use platform_core::{
+    ChangeEntity, SortOrder, Todo, TodoBulkAction, TodoBulkOutcome, TodoBulkRequest, TodoListQuery,
+    TodoSortField, TodoStatus,
+};
+use std::collections::HashSet;
 
 use crate::api;
-use crate::components::ui::{DataTable, Column};
+use crate::components::assignee::TodoPeople;
+use crate::components::todo_item::priority_variant;
+use crate::components::ui::{Alert, AlertVariant, Badge, BadgeSize, BadgeVariant, DataTable, Column, Pagination};
+
+const PAGE_SIZE: i64 = 20;
 
 #[component]
-fn DeleteButton(todo_id: uuid::Uuid) -> impl IntoView {
+fn DeleteButton(todo_id: uuid::Uuid, on_deleted: Callback<uuid::Uuid>) -> impl IntoView {
     let (deleting, set_deleting) = create_signal(false);
     
     let handle_delete = move |_| {
@@ -13,10 +21,7 @@ fn DeleteButton(todo_id: uuid::Uuid) -> impl IntoView {
         spawn_local(async move {
             match api::delete_todo(todo_id).await {
                 Ok(_) => {
-                    // Trigger a page reload to refresh the list
-                    if let Some(window) = web_sys::window() {
-                        let _ = window.location().reload();
-                    }
+                    on_deleted.call(todo_id);
                 }
                 Err(e) => {
                     log::error!("Failed to delete todo: {}", e);
@@ -43,13 +48,43 @@ pub fn AdminTodosPage() -> impl IntoView {
     let (loading, set_loading) = create_signal(true);
     let (error, set_error) = create_signal(None::<String>);
 
-    // Load todos on mount
+    // Filter, sort and paging state; any change triggers a refetch
+    let (status_filter, set_status_filter) = create_signal(None::<TodoStatus>);
+    let (search, set_search) = create_signal(String::new());
+    let (sort, set_sort) = create_signal(TodoSortField::CreatedAt);
+    let (order, set_order) = create_signal(SortOrder::Desc);
+    let (page, set_page) = create_signal(0_usize);
+    let (total_pages, set_total_pages) = create_signal(0_usize);
+    let (total, set_total) = create_signal(0_i64);
+
+    // Bumped to refetch the current page after a mutation
+    let (reload, set_reload) = create_signal(0_u32);
+
+    // Keys of the rows ticked in the table, used by the bulk action bar
+    let selection = create_rw_signal(HashSet::<String>::new());
+    let (bulk_running, set_bulk_running) = create_signal(false);
+    let (bulk_message, set_bulk_message) = create_signal(None::<String>);
+
+    // Load todos whenever the query changes
     create_effect(move |_| {
+        reload.track();
+        let query = TodoListQuery {
+            status: status_filter.get().into_iter().collect(),
+            q: Some(search.get()),
+            sort: sort.get(),
+            order: order.get(),
+            limit: Some(PAGE_SIZE),
+            offset: Some(page.get() as i64 * PAGE_SIZE),
+            ..Default::default()
+        };
+
         spawn_local(async move {
             set_loading.set(true);
-            match api::fetch_todos().await {
-                Ok(fetched_todos) => {
-                    set_todos.set(fetched_todos);
+            match api::fetch_todos(&query).await {
+                Ok(fetched) => {
+                    set_total.set(fetched.total);
+                    set_total_pages.set(fetched.total_pages());
+                    set_todos.set(fetched.items);
                     set_error.set(None);
                 }
                 Err(e) => {
@@ -60,15 +95,88 @@ pub fn AdminTodosPage() -> impl IntoView {
         });
     });
 
+    // Someone else changed a todo; show the current page as it is now
+    let feed = api::watch_changes(&[ChangeEntity::Todo], move |_| set_reload.update(|n| *n += 1));
+    on_cleanup(move || drop(feed));
+
+    let on_page_change = Callback::new(move |new_page: usize| set_page.set(new_page));
+
+    let on_todo_deleted = Callback::new(move |id: uuid::Uuid| {
+        selection.update(|keys| {
+            keys.remove(&id.to_string());
+        });
+        set_reload.update(|n| *n += 1);
+    });
+
+    let run_bulk_action = move |action: TodoBulkAction| {
+        let ids: Vec<uuid::Uuid> = selection
+            .get()
+            .iter()
+            .filter_map(|key| key.parse().ok())
+            .collect();
+        if ids.is_empty() {
+            return;
+        }
+
+        set_bulk_running.set(true);
+        set_bulk_message.set(None);
+        spawn_local(async move {
+            match api::bulk_todos(TodoBulkRequest { ids, action }).await {
+                Ok(response) => {
+                    let not_found = response
+                        .results
+                        .iter()
+                        .filter(|r| matches!(r.outcome, TodoBulkOutcome::NotFound))
+                        .count();
+                    let rejected = response
+                        .results
+                        .iter()
+                        .filter(|r| matches!(r.outcome, TodoBulkOutcome::Rejected { .. }))
+                        .count();
+                    let applied = response.results.len() - not_found - rejected;
+                    let mut message = format!("{} todos changed", applied);
+                    if not_found > 0 {
+                        message.push_str(&format!(", {} no longer exist", not_found));
+                    }
+                    if rejected > 0 {
+                        message.push_str(&format!(", {} cannot move to that status", rejected));
+                    }
+                    set_bulk_message.set(Some(message));
+                    selection.set(HashSet::new());
+                    set_reload.update(|n| *n += 1);
+                }
+                Err(e) => {
+                    set_bulk_message.set(Some(format!("Bulk action failed: {}", e)));
+                }
+            }
+            set_bulk_running.set(false);
+        });
+    };
+
     // Define table columns
     let columns = vec![
         Column::new("Title", |todo: &Todo| {
             view! {
-                <span class="font-medium text-slate-900">{todo.title.clone()}</span>
+                <div class="flex flex-col gap-1">
+                    <span class="font-medium text-slate-900">{todo.title.clone()}</span>
+                    <div class="flex flex-wrap gap-1">
+                        {todo
+                            .tags
+                            .iter()
+                            .map(|tag| {
+                                view! {
+                                    <Badge variant=BadgeVariant::Primary size=BadgeSize::Sm>
+                                        {format!("#{}", tag)}
+                                    </Badge>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                </div>
             }
             .into_view()
         })
-        .with_width("30%"),
+        .with_width("22%"),
         Column::new("Description", |todo: &Todo| {
             view! {
                 <span class="text-slate-600">
@@ -77,7 +185,7 @@ pub fn AdminTodosPage() -> impl IntoView {
             }
             .into_view()
         })
-        .with_width("35%"),
+        .with_width("20%"),
         Column::new("Status", |todo: &Todo| {
             let (bg_color, text_color) = match todo.status {
                 TodoStatus::Pending => ("bg-slate-100", "text-slate-700"),
@@ -92,7 +200,36 @@ pub fn AdminTodosPage() -> impl IntoView {
             }
             .into_view()
         })
-        .with_width("15%"),
+        .with_width("12%"),
+        Column::new("Priority", |todo: &Todo| {
+            view! {
+                <Badge variant=priority_variant(todo.priority) size=BadgeSize::Sm pill=true>
+                    {todo.priority.as_str()}
+                </Badge>
+            }
+            .into_view()
+        })
+        .with_width("8%"),
+        Column::new("Due", |todo: &Todo| {
+            let overdue = todo.is_overdue(chrono::Utc::now());
+            match todo.due_at {
+                Some(due) => view! {
+                    <Badge
+                        variant=if overdue { BadgeVariant::Danger } else { BadgeVariant::Secondary }
+                        size=BadgeSize::Sm
+                    >
+                        {due.format("%Y-%m-%d").to_string()}
+                    </Badge>
+                }
+                .into_view(),
+                None => view! { <span class="text-slate-400">"—"</span> }.into_view(),
+            }
+        })
+        .with_width("8%"),
+        Column::new("People", |todo: &Todo| {
+            view! { <TodoPeople owner_id=todo.owner_id assignee_id=todo.assignee_id/> }.into_view()
+        })
+        .with_width("10%"),
         Column::new("Created", |todo: &Todo| {
             view! {
                 <span class="text-slate-600 text-xs">
@@ -101,11 +238,11 @@ pub fn AdminTodosPage() -> impl IntoView {
             }
             .into_view()
         })
-        .with_width("12%"),
-        Column::new("Actions", |todo: &Todo| {
+        .with_width("8%"),
+        Column::new("Actions", move |todo: &Todo| {
             let todo_id = todo.id;
             view! {
-                <DeleteButton todo_id=todo_id/>
+                <DeleteButton todo_id=todo_id on_deleted=on_todo_deleted/>
             }
             .into_view()
         })
@@ -119,6 +256,138 @@ pub fn AdminTodosPage() -> impl IntoView {
                 <p class="text-slate-600">"Professional table view with data management"</p>
             </div>
 
+            <div class="flex flex-wrap items-center gap-3 mb-4">
+                <input
+                    type="search"
+                    prop:value=search
+                    on:input=move |ev| {
+                        set_search.set(event_target_value(&ev));
+                        set_page.set(0);
+                    }
+                    placeholder="Search title or description"
+                    class="flex-1 min-w-[200px] px-3 py-2 border-2 border-slate-200 rounded-lg text-sm focus:outline-none focus:border-blue-500"
+                />
+                <select
+                    on:change=move |ev| {
+                        set_status_filter.set(event_target_value(&ev).parse::<TodoStatus>().ok());
+                        set_page.set(0);
+                    }
+                    class="px-3 py-2 border-2 border-slate-200 rounded-lg text-sm bg-white"
+                >
+                    <option value="">"All statuses"</option>
+                    <option value="pending">"Pending"</option>
+                    <option value="in_progress">"In Progress"</option>
+                    <option value="completed">"Completed"</option>
+                    <option value="cancelled">"Cancelled"</option>
+                </select>
+                <select
+                    on:change=move |ev| {
+                        let field = match event_target_value(&ev).as_str() {
+                            "updated_at" => TodoSortField::UpdatedAt,
+                            "title" => TodoSortField::Title,
+                            "status" => TodoSortField::Status,
+                            "priority" => TodoSortField::Priority,
+                            "due_at" => TodoSortField::DueAt,
+                            _ => TodoSortField::CreatedAt,
+                        };
+                        set_sort.set(field);
+                        set_page.set(0);
+                    }
+                    class="px-3 py-2 border-2 border-slate-200 rounded-lg text-sm bg-white"
+                >
+                    <option value="created_at">"Sort: Created"</option>
+                    <option value="updated_at">"Sort: Updated"</option>
+                    <option value="title">"Sort: Title"</option>
+                    <option value="status">"Sort: Status"</option>
+                    <option value="priority">"Sort: Priority"</option>
+                    <option value="due_at">"Sort: Due date"</option>
+                </select>
+                <button
+                    on:click=move |_| {
+                        set_order.update(|o| {
+                            *o = match o {
+                                SortOrder::Asc => SortOrder::Desc,
+                                SortOrder::Desc => SortOrder::Asc,
+                            }
+                        });
+                        set_page.set(0);
+                    }
+                    class="px-3 py-2 border-2 border-slate-200 rounded-lg text-sm bg-white hover:bg-slate-50 transition-colors"
+                >
+                    {move || match order.get() {
+                        SortOrder::Asc => "↑ Asc",
+                        SortOrder::Desc => "↓ Desc",
+                    }}
+                </button>
+                <span class="text-sm text-slate-500">{move || format!("{} todos", total.get())}</span>
+            </div>
+
+            {move || {
+                let count = selection.with(|keys| keys.len());
+                if count > 0 {
+                    view! {
+                        <div class="flex flex-wrap items-center gap-2 mb-4 p-3 bg-blue-50 border border-blue-200 rounded-lg">
+                            <span class="text-sm font-semibold text-blue-900 mr-2">
+                                {format!("{} selected", count)}
+                            </span>
+                            <button
+                                on:click=move |_| run_bulk_action(TodoBulkAction::SetStatus { status: TodoStatus::Completed, reason: None })
+                                disabled=bulk_running
+                                class="px-3 py-1 bg-green-600 text-white rounded-lg text-xs font-semibold hover:bg-green-700 transition-colors disabled:opacity-60"
+                            >
+                                "Mark completed"
+                            </button>
+                            <button
+                                on:click=move |_| {
+                                    // Cancelling needs a reason; it is shared by every selected todo
+                                    if let Ok(Some(reason)) = window().prompt_with_message("Why are these todos being cancelled?") {
+                                        if !reason.trim().is_empty() {
+                                            run_bulk_action(TodoBulkAction::SetStatus {
+                                                status: TodoStatus::Cancelled,
+                                                reason: Some(reason),
+                                            });
+                                        }
+                                    }
+                                }
+                                disabled=bulk_running
+                                class="px-3 py-1 bg-slate-600 text-white rounded-lg text-xs font-semibold hover:bg-slate-700 transition-colors disabled:opacity-60"
+                            >
+                                "Mark cancelled"
+                            </button>
+                            <button
+                                on:click=move |_| run_bulk_action(TodoBulkAction::Delete)
+                                disabled=bulk_running
+                                class="px-3 py-1 bg-red-600 text-white rounded-lg text-xs font-semibold hover:bg-red-700 transition-colors disabled:opacity-60"
+                            >
+                                "Delete"
+                            </button>
+                            <button
+                                on:click=move |_| selection.set(HashSet::new())
+                                class="px-3 py-1 text-xs font-semibold text-slate-600 hover:text-slate-900"
+                            >
+                                "Clear selection"
+                            </button>
+                        </div>
+                    }.into_view()
+                } else {
+                    view! { <div/> }.into_view()
+                }
+            }}
+
+            {move || {
+                bulk_message.get().map(|message| {
+                    view! {
+                        <Alert
+                            variant=AlertVariant::Info
+                            on_close=Callback::new(move |_| set_bulk_message.set(None))
+                            class="mb-4".to_string()
+                        >
+                            {message}
+                        </Alert>
+                    }
+                })
+            }}
+
             {move || {
                 if let Some(err) = error.get() {
                     view! { 
@@ -133,11 +402,28 @@ pub fn AdminTodosPage() -> impl IntoView {
                             columns=columns.clone()
                             key_fn=|todo: &Todo| todo.id.to_string()
                             loading=loading.get()
-                            empty_message="No todos yet. Create one from the Home page!".to_string()
+                            empty_message="No todos match these filters.".to_string()
+                            selection=selection
                         />
                     }.into_view()
                 }
             }}
+
+            {move || {
+                let pages = total_pages.get();
+                if pages > 1 {
+                    view! {
+                        <Pagination
+                            current_page=page
+                            total_pages=pages
+                            on_page_change=on_page_change
+                            class="mt-6".to_string()
+                        />
+                    }.into_view()
+                } else {
+                    view! { <div/> }.into_view()
+                }
+            }}
         </div>
     }
 }
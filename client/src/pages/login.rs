@@ -0,0 +1,99 @@
+use leptos::*;
+use leptos_router::*;
+use platform_core::LoginRequest;
+
+use crate::api;
+use crate::auth::AuthContext;
+
+#[component]
+pub fn LoginPage() -> impl IntoView {
+    let auth_ctx = expect_context::<AuthContext>();
+    let navigate = use_navigate();
+
+    let (email, set_email) = create_signal(String::new());
+    let (password, set_password) = create_signal(String::new());
+    let (signing_in, set_signing_in) = create_signal(false);
+    let (error, set_error) = create_signal(None::<String>);
+
+    let handle_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+
+        let email_value = email.get();
+        let password_value = password.get();
+        if email_value.trim().is_empty() || password_value.is_empty() {
+            set_error.set(Some("Email and password are required".to_string()));
+            return;
+        }
+
+        let auth_ctx = auth_ctx.clone();
+        let navigate = navigate.clone();
+        set_signing_in.set(true);
+        set_error.set(None);
+
+        spawn_local(async move {
+            let request = LoginRequest {
+                email: email_value,
+                password: password_value,
+            };
+
+            match api::login(request).await {
+                Ok(login) => {
+                    // Also makes `App` load the theme settings it could not fetch while signed out
+                    auth_ctx.sign_in(&login.token, login.user);
+
+                    navigate("/", NavigateOptions::default());
+                }
+                Err(e) => {
+                    set_error.set(Some(e));
+                    set_signing_in.set(false);
+                }
+            }
+        });
+    };
+
+    view! {
+        <div class="min-h-screen flex items-center justify-center bg-slate-50 p-8">
+            <div class="w-full max-w-sm bg-white p-8 rounded-xl shadow-md">
+                <h1 class="text-2xl font-bold mb-1" style="color: var(--color-primary);">"Atom Platform"</h1>
+                <p class="text-sm text-slate-500 mb-6">"Sign in to continue"</p>
+                <form on:submit=handle_submit class="space-y-4">
+                    <input
+                        type="email"
+                        prop:value=email
+                        on:input=move |ev| set_email.set(event_target_value(&ev))
+                        placeholder="Email"
+                        autocomplete="username"
+                        disabled=signing_in
+                        style="border-color: #e2e8f0;"
+                        class="w-full px-4 py-3 border-2 rounded-lg focus:outline-none transition-colors disabled:opacity-60 focus:border-primary"
+                    />
+                    <input
+                        type="password"
+                        prop:value=password
+                        on:input=move |ev| set_password.set(event_target_value(&ev))
+                        placeholder="Password"
+                        autocomplete="current-password"
+                        disabled=signing_in
+                        style="border-color: #e2e8f0;"
+                        class="w-full px-4 py-3 border-2 rounded-lg focus:outline-none transition-colors disabled:opacity-60 focus:border-primary"
+                    />
+                    {move || {
+                        error
+                            .get()
+                            .map(|err| {
+                                view! { <div class="text-red-600 text-sm">{err}</div> }
+                            })
+                    }}
+                    <button
+                        type="submit"
+                        disabled=signing_in
+                        style="background-color: var(--color-primary);"
+                        class="w-full py-3 text-white rounded-lg font-semibold hover:opacity-90 transition-all disabled:opacity-60 disabled:cursor-not-allowed"
+                    >
+                        {move || if signing_in.get() { "Signing in..." } else { "Sign in" }}
+                    </button>
+                </form>
+            </div>
+        </div>
+    }
+}
@@ -1,9 +1,17 @@
 pub mod home;
 pub mod admin_todos;
 pub mod settings_theme;
+pub mod settings_tokens;
 pub mod component_showcase;
+pub mod todo_detail;
+pub mod trash;
+pub mod login;
 
 pub use home::HomePage;
 pub use admin_todos::AdminTodosPage;
 pub use settings_theme::SettingsThemePage;
+pub use settings_tokens::SettingsTokensPage;
 pub use component_showcase::ComponentShowcasePage;
+pub use todo_detail::TodoDetailPage;
+pub use trash::TrashPage;
+pub use login::LoginPage;
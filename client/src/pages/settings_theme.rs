@@ -1,7 +1,9 @@
 use leptos::*;
-use platform_core::{Theme, ThemeWithSettings, ThemeCreate, ThemeUpdate, ComponentSetting};
+use platform_core::{setting_value_error, FieldError, Theme, ThemeWithSettings, ThemeCreate, ThemeUpdate, ComponentSetting, Validate};
 
 use crate::api;
+use crate::components::conflict_dialog::ConflictDialog;
+use crate::components::field_error::FieldErrorText;
 use crate::components::ui::{Card, TabList, Tab, TabPanel};
 use crate::theme::ThemeContext;
 
@@ -105,9 +107,10 @@ pub fn SettingsThemePage() -> impl IntoView {
                                     let theme_id = theme.id;
                                     let theme_name = theme.name.clone();
                                     let is_active = theme.is_active;
+                                    let version = theme.version;
                                     view! {
                                         <div class=move || if active_tab.get() == idx { "block" } else { "hidden" }>
-                                            <ThemeEditor theme_id=theme_id theme_name=theme_name is_active=is_active/>
+                                            <ThemeEditor theme_id=theme_id theme_name=theme_name is_active=is_active version=version/>
                                         </div>
                                     }
                                 }
@@ -125,11 +128,17 @@ fn ThemeEditor(
     theme_id: uuid::Uuid,
     theme_name: String,
     is_active: bool,
+    version: i32,
 ) -> impl IntoView {
     let (settings, set_settings) = create_signal(Vec::<ComponentSetting>::new());
     let (loading, set_loading) = create_signal(true);
     let (editing_name, set_editing_name) = create_signal(false);
-    let (new_name, set_new_name) = create_signal(theme_name.clone());
+    let (name, set_name) = create_signal(theme_name.clone());
+    let (new_name, set_new_name) = create_signal(theme_name);
+    let (version, set_version) = create_signal(version);
+    // An update refused because the theme changed meanwhile, kept for "Overwrite"
+    let (conflict, set_conflict) = create_signal(None::<ThemeUpdate>);
+    let (name_errors, set_name_errors) = create_signal(Vec::<FieldError>::new());
 
     // Load theme settings
     create_effect(move |_| {
@@ -147,45 +156,77 @@ fn ThemeEditor(
         });
     });
 
-    let handle_activate = move |_| {
+    // Sends `update` against the version this editor last saw
+    let send_update = move |update: ThemeUpdate| {
         spawn_local(async move {
-            let update = ThemeUpdate {
-                name: None,
-                is_active: Some(true),
-            };
-
-            match api::update_theme(theme_id, update).await {
-                Ok(_) => {
-                    if let Some(window) = web_sys::window() {
-                        let _ = window.location().reload();
+            match api::update_theme(theme_id, version.get_untracked(), update.clone()).await {
+                Ok(theme) => {
+                    if update.is_active == Some(true) {
+                        if let Some(window) = web_sys::window() {
+                            let _ = window.location().reload();
+                        }
                     }
+                    set_version.set(theme.version);
+                    set_name.set(theme.name);
+                    set_editing_name.set(false);
+                }
+                Err(e) if e == api::EDIT_CONFLICT => {
+                    set_conflict.set(Some(update));
                 }
                 Err(e) => {
-                    log::error!("Failed to activate theme: {}", e);
+                    log::error!("Failed to update theme: {}", e);
                 }
             }
         });
     };
 
-    let handle_save_name = move |_| {
-        let name = new_name.get();
+    // Loads the latest version, then either shows it or re-sends the refused update on top of it
+    let resolve_conflict = move |overwrite: bool| {
+        let Some(pending) = conflict.get_untracked() else {
+            return;
+        };
+        set_conflict.set(None);
         spawn_local(async move {
-            let update = ThemeUpdate {
-                name: Some(name),
-                is_active: None,
-            };
-
-            match api::update_theme(theme_id, update).await {
-                Ok(_) => {
+            match api::fetch_theme_with_settings(theme_id).await {
+                Ok(latest) if overwrite => {
+                    set_version.set(latest.theme.version);
+                    send_update(pending);
+                }
+                Ok(latest) => {
+                    set_version.set(latest.theme.version);
+                    set_name.set(latest.theme.name.clone());
+                    set_new_name.set(latest.theme.name);
+                    set_settings.set(latest.settings);
                     set_editing_name.set(false);
                 }
                 Err(e) => {
-                    log::error!("Failed to update theme name: {}", e);
+                    log::error!("Failed to reload theme: {}", e);
                 }
             }
         });
     };
 
+    let handle_activate = move |_| {
+        send_update(ThemeUpdate {
+            name: None,
+            is_active: Some(true),
+        });
+    };
+
+    let handle_save_name = move |_| {
+        let update = ThemeUpdate {
+            name: Some(new_name.get()),
+            is_active: None,
+        };
+        match update.validate() {
+            Ok(()) => {
+                set_name_errors.set(Vec::new());
+                send_update(update);
+            }
+            Err(fields) => set_name_errors.set(fields),
+        }
+    };
+
     let grouped_settings = move || {
         let all_settings = settings.get();
         let mut groups: std::collections::HashMap<String, Vec<ComponentSetting>> = std::collections::HashMap::new();
@@ -206,32 +247,35 @@ fn ThemeEditor(
             <div class="flex items-center gap-4 p-4 bg-white dark:bg-slate-800 rounded-lg shadow-md">
                 {move || if editing_name.get() {
                     view! {
-                        <div class="flex-1 flex gap-2">
-                            <input
-                                type="text"
-                                prop:value=new_name
-                                on:input=move |ev| set_new_name.set(event_target_value(&ev))
-                                class="flex-1 px-3 py-2 border-2 border-slate-200 dark:border-slate-600 rounded-lg bg-white dark:bg-slate-700 text-slate-900 dark:text-slate-100"
-                                placeholder="Theme name"
-                            />
-                            <button
-                                on:click=handle_save_name
-                                class="px-4 py-2 bg-blue-600 text-white rounded-lg hover:bg-blue-700 transition-colors"
-                            >
-                                "Save"
-                            </button>
-                            <button
-                                on:click=move |_| set_editing_name.set(false)
-                                class="px-4 py-2 bg-slate-600 text-white rounded-lg hover:bg-slate-700 transition-colors"
-                            >
-                                "Cancel"
-                            </button>
+                        <div class="flex-1">
+                            <div class="flex gap-2">
+                                <input
+                                    type="text"
+                                    prop:value=new_name
+                                    on:input=move |ev| set_new_name.set(event_target_value(&ev))
+                                    class="flex-1 px-3 py-2 border-2 border-slate-200 dark:border-slate-600 rounded-lg bg-white dark:bg-slate-700 text-slate-900 dark:text-slate-100"
+                                    placeholder="Theme name"
+                                />
+                                <button
+                                    on:click=handle_save_name
+                                    class="px-4 py-2 bg-blue-600 text-white rounded-lg hover:bg-blue-700 transition-colors"
+                                >
+                                    "Save"
+                                </button>
+                                <button
+                                    on:click=move |_| set_editing_name.set(false)
+                                    class="px-4 py-2 bg-slate-600 text-white rounded-lg hover:bg-slate-700 transition-colors"
+                                >
+                                    "Cancel"
+                                </button>
+                            </div>
+                            <FieldErrorText errors=name_errors field="name" label="Name"/>
                         </div>
                     }.into_view()
                 } else {
                     view! {
                         <div class="flex-1 flex items-center gap-4">
-                            <h2 class="text-xl font-semibold text-slate-800 dark:text-slate-100">{theme_name.clone()}</h2>
+                            <h2 class="text-xl font-semibold text-slate-800 dark:text-slate-100">{name}</h2>
                             {if !is_active {
                                 view! {
                                     <button
@@ -303,6 +347,13 @@ fn ThemeEditor(
                 }
             }}
         </div>
+        <ConflictDialog
+            open=Signal::derive(move || conflict.with(Option::is_some))
+            subject="theme"
+            on_reload=Callback::new(move |_| resolve_conflict(false))
+            on_overwrite=Callback::new(move |_| resolve_conflict(true))
+            on_close=Callback::new(move |_| set_conflict.set(None))
+        />
     }
 }
 
@@ -313,30 +364,69 @@ fn ThemeSettingItem(
 ) -> impl IntoView {
     let (value, set_value) = create_signal(setting.value.clone());
     let (is_updating, set_is_updating) = create_signal(false);
+    let (version, set_version) = create_signal(setting.version);
+    let (conflict, set_conflict) = create_signal(false);
     let setting_id = setting.id;
     let setting_key = setting.key.clone();
+    let category = setting.category.clone();
+    // Checked as the user types, with the rule the server applies to this category
+    let value_error = Signal::derive(move || setting_value_error(&category, &value.get()));
 
     let theme = use_context::<ThemeContext>().expect("ThemeContext not found");
 
-    let handle_save = move |_| {
-        let new_value = value.get();
+    // Saves the current value against the version this item last saw
+    let save = Callback::new(move |_: ()| {
+        let new_value = value.get_untracked();
         let key = setting_key.clone();
         let theme = theme.clone();
         set_is_updating.set(true);
-        
+
         spawn_local(async move {
-            match api::update_theme_setting(theme_id, setting_id, new_value.clone()).await {
-                Ok(_) => {
+            match api::update_theme_setting(theme_id, setting_id, version.get_untracked(), new_value.clone()).await {
+                Ok(saved) => {
+                    set_version.set(saved.version);
                     // Update theme context and apply
                     theme.set(key.clone(), new_value);
                     theme.apply_to_document();
                 }
+                Err(e) if e == api::EDIT_CONFLICT => {
+                    set_conflict.set(true);
+                }
                 Err(e) => {
                     log::error!("Failed to update setting: {}", e);
                 }
             }
             set_is_updating.set(false);
         });
+    });
+
+    let handle_save = move |_| {
+        if value_error.get_untracked().is_none() {
+            save.call(());
+        }
+    };
+
+    // Loads the latest value, then either shows it or saves the local value on top of it
+    let resolve_conflict = move |overwrite: bool| {
+        set_conflict.set(false);
+        spawn_local(async move {
+            let latest = match api::fetch_theme_with_settings(theme_id).await {
+                Ok(latest) => latest.settings.into_iter().find(|s| s.id == setting_id),
+                Err(e) => {
+                    log::error!("Failed to reload setting: {}", e);
+                    return;
+                }
+            };
+            let Some(latest) = latest else {
+                return;
+            };
+            set_version.set(latest.version);
+            if overwrite {
+                save.call(());
+            } else {
+                set_value.set(latest.value);
+            }
+        });
     };
 
     let is_color = setting.key.starts_with("color_");
@@ -375,13 +465,16 @@ fn ThemeSettingItem(
                 
                 <button
                     on:click=handle_save
-                    disabled=is_updating.get()
+                    disabled=move || is_updating.get() || value_error.with(Option::is_some)
                     style="background-color: var(--color-primary);"
                     class="px-4 py-2 text-white rounded-lg text-sm font-semibold hover:opacity-90 transition-all disabled:opacity-60 disabled:cursor-not-allowed"
                 >
                     {move || if is_updating.get() { "Saving..." } else { "Save" }}
                 </button>
             </div>
+            {move || value_error.get().map(|message| view! {
+                <p class="mt-1 text-sm text-red-600">{format!("Value {}", message)}</p>
+            })}
             
             {if is_color {
                 view! {
@@ -396,5 +489,12 @@ fn ThemeSettingItem(
                 view! { <div/> }.into_view()
             }}
         </div>
+        <ConflictDialog
+            open=conflict
+            subject="theme setting"
+            on_reload=Callback::new(move |_| resolve_conflict(false))
+            on_overwrite=Callback::new(move |_| resolve_conflict(true))
+            on_close=Callback::new(move |_| set_conflict.set(false))
+        />
     }
 }
@@ -0,0 +1,269 @@
+use leptos::*;
+use platform_core::{ApiToken, ApiTokenCreate, FieldError, Permission, Validate};
+use std::collections::HashSet;
+
+use crate::api;
+use crate::auth::AuthContext;
+use crate::components::field_error::FieldErrorText;
+use crate::components::ui::{Alert, AlertVariant, Badge, BadgeSize, BadgeVariant, Card};
+
+/// What each scope lets a script do
+fn scope_description(scope: Permission) -> &'static str {
+    match scope {
+        Permission::Read => "Read todos, themes and settings",
+        Permission::EditTodos => "Create, edit and delete todos",
+        Permission::ManageTodos => "Bulk actions and the trash",
+        Permission::ManageSettings => "Themes and settings",
+        Permission::ManageUsers => "Accounts and roles",
+    }
+}
+
+#[component]
+fn TokenRow(token: ApiToken, on_revoked: Callback<uuid::Uuid>) -> impl IntoView {
+    let (revoking, set_revoking) = create_signal(false);
+    let token_id = token.id;
+    let expired = token.is_expired(chrono::Utc::now());
+
+    let handle_revoke = move |_| {
+        if !window()
+            .confirm_with_message("Revoke this token? Scripts using it will stop working.")
+            .unwrap_or(false)
+        {
+            return;
+        }
+        set_revoking.set(true);
+        spawn_local(async move {
+            match api::revoke_token(token_id).await {
+                Ok(()) => on_revoked.call(token_id),
+                Err(e) => {
+                    log::error!("Failed to revoke token: {}", e);
+                    set_revoking.set(false);
+                }
+            }
+        });
+    };
+
+    view! {
+        <div class="flex flex-wrap items-center gap-4 py-3 border-b border-slate-100 last:border-0">
+            <div class="flex-1 min-w-[200px]">
+                <p class="font-semibold text-slate-800">{token.name.clone()}</p>
+                <p class="text-xs font-mono text-slate-500">{format!("{}…", token.prefix)}</p>
+                <div class="flex flex-wrap gap-1 mt-1">
+                    {token
+                        .scopes
+                        .iter()
+                        .map(|scope| view! {
+                            <Badge variant=BadgeVariant::Primary size=BadgeSize::Sm>{scope.as_str()}</Badge>
+                        })
+                        .collect_view()}
+                </div>
+            </div>
+            <div class="text-xs text-slate-500 space-y-1">
+                <p>{format!("Created {}", token.created_at.format("%Y-%m-%d"))}</p>
+                <p>
+                    {token
+                        .last_used_at
+                        .map(|at| format!("Last used {}", at.format("%Y-%m-%d %H:%M")))
+                        .unwrap_or_else(|| "Never used".to_string())}
+                </p>
+                <p class=if expired { "text-red-600 font-semibold" } else { "" }>
+                    {match token.expires_at {
+                        Some(at) if expired => format!("Expired {}", at.format("%Y-%m-%d")),
+                        Some(at) => format!("Expires {}", at.format("%Y-%m-%d")),
+                        None => "Never expires".to_string(),
+                    }}
+                </p>
+            </div>
+            <button
+                on:click=handle_revoke
+                disabled=revoking
+                class="px-3 py-1 bg-red-600 text-white rounded-lg text-xs font-semibold hover:bg-red-700 transition-colors disabled:opacity-60"
+            >
+                {move || if revoking.get() { "Revoking..." } else { "Revoke" }}
+            </button>
+        </div>
+    }
+}
+
+#[component]
+pub fn SettingsTokensPage() -> impl IntoView {
+    let user = expect_context::<AuthContext>().user;
+
+    let (tokens, set_tokens) = create_signal(Vec::<ApiToken>::new());
+    let (loading, set_loading) = create_signal(true);
+    let (error, set_error) = create_signal(None::<String>);
+    let (field_errors, set_field_errors) = create_signal(Vec::<FieldError>::new());
+
+    let (name, set_name) = create_signal(String::new());
+    let scopes = create_rw_signal(HashSet::from([Permission::Read]));
+    let (expires_in_days, set_expires_in_days) = create_signal(Some(90_u32));
+    let (creating, set_creating) = create_signal(false);
+    // Shown once right after creation; the server keeps only a hash
+    let (new_secret, set_new_secret) = create_signal(None::<String>);
+
+    create_effect(move |_| {
+        spawn_local(async move {
+            set_loading.set(true);
+            match api::fetch_tokens().await {
+                Ok(fetched) => set_tokens.set(fetched),
+                Err(e) => set_error.set(Some(e)),
+            }
+            set_loading.set(false);
+        });
+    });
+
+    let handle_create = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+
+        let request = ApiTokenCreate {
+            name: name.get(),
+            scopes: Permission::ALL
+                .into_iter()
+                .filter(|scope| scopes.with(|scopes| scopes.contains(scope)))
+                .collect(),
+            expires_in_days: expires_in_days.get(),
+        };
+        if let Err(fields) = request.validate() {
+            set_field_errors.set(fields);
+            return;
+        }
+
+        set_creating.set(true);
+        set_error.set(None);
+        set_field_errors.set(Vec::new());
+        spawn_local(async move {
+            match api::create_token(request).await {
+                Ok(created) => {
+                    set_tokens.update(|tokens| tokens.insert(0, created.token));
+                    set_new_secret.set(Some(created.secret));
+                    set_name.set(String::new());
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+            set_creating.set(false);
+        });
+    };
+
+    let on_revoked = Callback::new(move |id: uuid::Uuid| {
+        set_tokens.update(|tokens| tokens.retain(|token| token.id != id));
+    });
+
+    view! {
+        <div class="max-w-3xl space-y-6">
+            <div>
+                <h1 class="text-3xl font-bold text-slate-800 mb-2">"API Tokens"</h1>
+                <p class="text-slate-600">
+                    "Personal access tokens let scripts and CI jobs call the API as you. Send one as "
+                    <code class="text-sm">"Authorization: Bearer <token>"</code>"."
+                </p>
+            </div>
+
+            {move || {
+                new_secret.get().map(|secret| {
+                    view! {
+                        <Alert
+                            variant=AlertVariant::Success
+                            title="Copy your new token now".to_string()
+                            on_close=Callback::new(move |_| set_new_secret.set(None))
+                        >
+                            <p class="mb-2">"It will not be shown again."</p>
+                            <input
+                                type="text"
+                                readonly=true
+                                prop:value=secret
+                                class="w-full px-3 py-2 font-mono text-sm border-2 border-green-200 rounded-lg bg-white"
+                            />
+                        </Alert>
+                    }
+                })
+            }}
+
+            <Card>
+                <h2 class="text-xl font-semibold text-slate-800 mb-4">"New token"</h2>
+                <form on:submit=handle_create class="space-y-4">
+                    <input
+                        type="text"
+                        prop:value=name
+                        on:input=move |ev| set_name.set(event_target_value(&ev))
+                        placeholder="Name, e.g. \"CI theme export\""
+                        disabled=creating
+                        class="w-full px-4 py-2 border-2 border-slate-200 rounded-lg focus:outline-none focus:border-blue-500 disabled:opacity-60"
+                    />
+                    <FieldErrorText errors=field_errors field="name" label="Name"/>
+                    <div class="space-y-2">
+                        // A token cannot do more than its owner, so only offer what the role allows
+                        {move || {
+                            let current = user.get();
+                            Permission::ALL
+                                .into_iter()
+                                .filter(|scope| current.as_ref().is_some_and(|user| user.can(*scope)))
+                                .map(|scope| {
+                                    view! {
+                                        <label class="flex items-center gap-2 text-sm text-slate-700">
+                                            <input
+                                                type="checkbox"
+                                                prop:checked=move || scopes.with(|scopes| scopes.contains(&scope))
+                                                on:change=move |ev| {
+                                                    let checked = event_target_checked(&ev);
+                                                    scopes.update(|scopes| {
+                                                        if checked {
+                                                            scopes.insert(scope);
+                                                        } else {
+                                                            scopes.remove(&scope);
+                                                        }
+                                                    });
+                                                }
+                                            />
+                                            <span class="font-mono">{scope.as_str()}</span>
+                                            <span class="text-slate-500">{scope_description(scope)}</span>
+                                        </label>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+                        <FieldErrorText errors=field_errors field="scopes" label="Scopes"/>
+                    </div>
+                    <select
+                        on:change=move |ev| set_expires_in_days.set(event_target_value(&ev).parse().ok())
+                        disabled=creating
+                        class="px-4 py-2 border-2 border-slate-200 rounded-lg bg-white text-sm"
+                    >
+                        <option value="30">"Expires in 30 days"</option>
+                        <option value="90" selected=true>"Expires in 90 days"</option>
+                        <option value="365">"Expires in a year"</option>
+                        <option value="">"Never expires"</option>
+                    </select>
+                    {move || error.get().map(|err| view! { <div class="text-red-600 text-sm">{err}</div> })}
+                    <button
+                        type="submit"
+                        disabled=creating
+                        style="background-color: var(--color-primary);"
+                        class="px-4 py-2 text-white rounded-lg font-semibold hover:opacity-90 transition-all disabled:opacity-60"
+                    >
+                        {move || if creating.get() { "Creating..." } else { "Create token" }}
+                    </button>
+                </form>
+            </Card>
+
+            <Card>
+                <h2 class="text-xl font-semibold text-slate-800 mb-2">"Your tokens"</h2>
+                {move || {
+                    if loading.get() {
+                        view! { <p class="text-slate-500">"Loading tokens..."</p> }.into_view()
+                    } else if tokens.with(Vec::is_empty) {
+                        view! { <p class="text-slate-500">"You have no API tokens."</p> }.into_view()
+                    } else {
+                        view! {
+                            <For
+                                each=move || tokens.get()
+                                key=|token| token.id
+                                children=move |token| view! { <TokenRow token=token on_revoked=on_revoked/> }
+                            />
+                        }
+                        .into_view()
+                    }
+                }}
+            </Card>
+        </div>
+    }
+}
@@ -0,0 +1,79 @@
+use leptos::*;
+use leptos_router::*;
+use platform_core::Todo;
+use uuid::Uuid;
+
+use crate::api;
+use crate::components::todo_history::TodoHistory;
+use crate::components::todo_item::TodoItem;
+use crate::components::ui::{BreadcrumbItem, Breadcrumbs};
+
+#[component]
+pub fn TodoDetailPage() -> impl IntoView {
+    let params = use_params_map();
+    let todo_id = move || params.with(|p| p.get("id").and_then(|id| id.parse::<Uuid>().ok()));
+
+    let (todo, set_todo) = create_signal(None::<Todo>);
+    let (error, set_error) = create_signal(None::<String>);
+
+    // Bumped after every edit so the history panel picks up the new event
+    let (history_reload, set_history_reload) = create_signal(0_u32);
+
+    create_effect(move |_| {
+        let Some(id) = todo_id() else {
+            set_error.set(Some("Invalid todo id".to_string()));
+            return;
+        };
+        spawn_local(async move {
+            match api::fetch_todo(id).await {
+                Ok(fetched) => {
+                    set_todo.set(Some(fetched));
+                    set_error.set(None);
+                }
+                Err(e) => {
+                    set_error.set(Some(e));
+                }
+            }
+        });
+    });
+
+    let on_updated = Callback::new(move |_: Todo| set_history_reload.update(|n| *n += 1));
+    let navigate = use_navigate();
+    let on_deleted = Callback::new(move |_: Uuid| navigate("/", Default::default()));
+
+    let breadcrumbs = vec![
+        BreadcrumbItem {
+            label: "Home".to_string(),
+            href: Some("/".to_string()),
+        },
+        BreadcrumbItem {
+            label: "Todo".to_string(),
+            href: None,
+        },
+    ];
+
+    view! {
+        <div class="max-w-3xl mx-auto space-y-6">
+            <Breadcrumbs items=breadcrumbs/>
+            {move || {
+                if let Some(err) = error.get() {
+                    view! {
+                        <div class="text-center p-8 bg-white rounded-xl shadow-md text-red-600">
+                            "Error: " {err}
+                        </div>
+                    }
+                    .into_view()
+                } else if let Some(current) = todo.get() {
+                    let id = current.id;
+                    view! {
+                        <TodoItem todo=current on_updated=on_updated on_deleted=on_deleted/>
+                        <TodoHistory todo_id=id reload=history_reload/>
+                    }
+                    .into_view()
+                } else {
+                    view! { <div class="text-center p-8 text-slate-500">"Loading..."</div> }.into_view()
+                }
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,177 @@
+use leptos::*;
+use platform_core::{Theme, Todo, TodoListQuery, TodoSortField, MAX_PAGE_LIMIT};
+
+use crate::api;
+use crate::components::ui::{Alert, AlertVariant, Column, DataTable};
+
+/// Shared "Deleted" column renderer for trashed rows
+fn deleted_cell(deleted_at: Option<chrono::DateTime<chrono::Utc>>) -> View {
+    view! {
+        <span class="text-slate-600 text-xs">
+            {deleted_at
+                .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default()}
+        </span>
+    }
+    .into_view()
+}
+
+#[component]
+fn RestoreButton(on_restore: Callback<()>) -> impl IntoView {
+    let (restoring, set_restoring) = create_signal(false);
+
+    view! {
+        <button
+            on:click=move |_| {
+                set_restoring.set(true);
+                on_restore.call(());
+            }
+            disabled=restoring
+            class="px-3 py-1 bg-blue-600 text-white rounded-lg text-xs font-semibold hover:bg-blue-700 transition-colors disabled:opacity-60"
+        >
+            {move || if restoring.get() { "Restoring..." } else { "Restore" }}
+        </button>
+    }
+}
+
+#[component]
+pub fn TrashPage() -> impl IntoView {
+    let (todos, set_todos) = create_signal(Vec::<Todo>::new());
+    let (themes, set_themes) = create_signal(Vec::<Theme>::new());
+    let (loading, set_loading) = create_signal(true);
+    let (message, set_message) = create_signal(None::<(AlertVariant, String)>);
+
+    // Bumped to refetch both lists after a restore
+    let (reload, set_reload) = create_signal(0_u32);
+
+    create_effect(move |_| {
+        reload.track();
+        let query = TodoListQuery {
+            only_deleted: true,
+            sort: TodoSortField::UpdatedAt,
+            limit: Some(MAX_PAGE_LIMIT),
+            ..Default::default()
+        };
+
+        spawn_local(async move {
+            set_loading.set(true);
+            match api::fetch_todos(&query).await {
+                Ok(page) => set_todos.set(page.items),
+                Err(e) => set_message.set(Some((AlertVariant::Danger, e))),
+            }
+            match api::fetch_deleted_themes().await {
+                Ok(fetched) => set_themes.set(fetched),
+                Err(e) => set_message.set(Some((AlertVariant::Danger, e))),
+            }
+            set_loading.set(false);
+        });
+    });
+
+    let restore_todo = move |todo: Todo| {
+        spawn_local(async move {
+            match api::restore_todo(todo.id).await {
+                Ok(_) => set_message.set(Some((AlertVariant::Success, format!("Restored \"{}\"", todo.title)))),
+                Err(e) => set_message.set(Some((AlertVariant::Danger, format!("Failed to restore todo: {}", e)))),
+            }
+            set_reload.update(|n| *n += 1);
+        });
+    };
+
+    let restore_theme = move |theme: Theme| {
+        spawn_local(async move {
+            match api::restore_theme(theme.id).await {
+                Ok(_) => set_message.set(Some((AlertVariant::Success, format!("Restored theme \"{}\"", theme.name)))),
+                Err(e) => set_message.set(Some((AlertVariant::Danger, format!("Failed to restore theme: {}", e)))),
+            }
+            set_reload.update(|n| *n += 1);
+        });
+    };
+
+    let todo_columns = vec![
+        Column::new("Title", |todo: &Todo| {
+            view! { <span class="font-medium text-slate-900">{todo.title.clone()}</span> }.into_view()
+        })
+        .with_width("50%"),
+        Column::new("Status", |todo: &Todo| {
+            view! { <span class="text-slate-600">{format!("{:?}", todo.status)}</span> }.into_view()
+        })
+        .with_width("15%"),
+        Column::new("Deleted", |todo: &Todo| deleted_cell(todo.deleted_at)).with_width("20%"),
+        Column::new("Actions", move |todo: &Todo| {
+            let todo = todo.clone();
+            view! {
+                <RestoreButton on_restore=Callback::new(move |_| restore_todo(todo.clone()))/>
+            }
+            .into_view()
+        })
+        .with_width("15%"),
+    ];
+
+    let theme_columns = vec![
+        Column::new("Name", |theme: &Theme| {
+            view! { <span class="font-medium text-slate-900">{theme.name.clone()}</span> }.into_view()
+        })
+        .with_width("65%"),
+        Column::new("Deleted", |theme: &Theme| deleted_cell(theme.deleted_at)).with_width("20%"),
+        Column::new("Actions", move |theme: &Theme| {
+            let theme = theme.clone();
+            view! {
+                <RestoreButton on_restore=Callback::new(move |_| restore_theme(theme.clone()))/>
+            }
+            .into_view()
+        })
+        .with_width("15%"),
+    ];
+
+    view! {
+        <div>
+            <div class="mb-6">
+                <h1 class="text-3xl font-bold text-slate-800 mb-2">"Trash"</h1>
+                <p class="text-slate-600">
+                    "Deleted todos and themes are kept here until they are purged."
+                </p>
+            </div>
+
+            {move || {
+                message.get().map(|(variant, text)| {
+                    view! {
+                        <Alert
+                            variant=variant
+                            on_close=Callback::new(move |_| set_message.set(None))
+                            class="mb-4".to_string()
+                        >
+                            {text}
+                        </Alert>
+                    }
+                })
+            }}
+
+            <h2 class="text-xl font-semibold text-slate-800 mb-3">"Todos"</h2>
+            {move || {
+                view! {
+                    <DataTable
+                        data=Signal::derive(move || todos.get())
+                        columns=todo_columns.clone()
+                        key_fn=|todo: &Todo| todo.id.to_string()
+                        loading=loading.get()
+                        empty_message="No deleted todos.".to_string()
+                        class="mb-8".to_string()
+                    />
+                }
+            }}
+
+            <h2 class="text-xl font-semibold text-slate-800 mb-3">"Themes"</h2>
+            {move || {
+                view! {
+                    <DataTable
+                        data=Signal::derive(move || themes.get())
+                        columns=theme_columns.clone()
+                        key_fn=|theme: &Theme| theme.id.to_string()
+                        loading=loading.get()
+                        empty_message="No deleted themes.".to_string()
+                    />
+                }
+            }}
+        </div>
+    }
+}
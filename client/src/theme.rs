@@ -3,6 +3,8 @@ use platform_core::ComponentSetting;
 use std::collections::HashMap;
 use wasm_bindgen::JsCast;
 
+use crate::api;
+
 const THEME_STORAGE_KEY: &str = "atom_theme_settings";
 
 #[derive(Clone, Debug)]
@@ -64,6 +66,37 @@ impl ThemeContext {
         self.save_to_local_storage();
     }
 
+    /// Replaces the settings with the active theme's and applies them
+    pub async fn load_active_theme(&self) -> Result<(), String> {
+        let themes = api::fetch_themes().await?;
+        let Some(active) = themes.into_iter().find(|theme| theme.is_active) else {
+            return Ok(());
+        };
+        let active = api::fetch_theme_with_settings(active.id).await?;
+        self.load_from_api(active.settings);
+        self.apply_to_document();
+        Ok(())
+    }
+
+    /// Saves `value` as the active theme's `key` setting, for every client
+    pub async fn save_to_active_theme(&self, key: &str, value: String) -> Result<(), String> {
+        let themes = api::fetch_themes().await?;
+        let active = themes
+            .into_iter()
+            .find(|theme| theme.is_active)
+            .ok_or_else(|| "No theme is active".to_string())?;
+        let active = api::fetch_theme_with_settings(active.id).await?;
+        let setting = active
+            .settings
+            .into_iter()
+            .find(|setting| setting.key == key)
+            .ok_or_else(|| format!("The active theme has no {} setting", key))?;
+        api::update_theme_setting(active.theme.id, setting.id, setting.version, value.clone()).await?;
+        self.set(key.to_string(), value);
+        self.apply_to_document();
+        Ok(())
+    }
+
     pub fn get_theme_mode(&self) -> String {
         self.settings
             .get()
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kind of record a [`ChangeEvent`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeEntity {
+    Todo,
+    Theme,
+    ThemeSetting,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    /// Inserted, or restored from the trash
+    Created,
+    Updated,
+    /// Moved to the trash or removed for good
+    Deleted,
+}
+
+/// One message of the live change feed at `/api/v1/events`
+///
+/// Says which record changed, not how; clients fetch the record or list
+/// again to see the new state.
+///
+/// ```json
+/// { "entity": "todo", "action": "updated", "id": "6f1c...", "version": 4 }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ChangeEvent {
+    pub entity: ChangeEntity,
+    pub action: ChangeAction,
+    pub id: Uuid,
+    /// Theme the setting belongs to; only set for `theme_setting`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme_id: Option<Uuid>,
+    /// Todo the subtask belongs to; only set for subtasks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Uuid>,
+    /// Row version after the change, or the last one for deletions
+    pub version: i32,
+}
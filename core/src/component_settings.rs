@@ -2,8 +2,11 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::Patch;
+
 /// Component setting item
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
 pub struct ComponentSetting {
     pub id: Uuid,
@@ -13,6 +16,8 @@ pub struct ComponentSetting {
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Incremented on every write; sent back as the `ETag`
+    pub version: i32,
 }
 
 /// Request body for creating a component setting
@@ -26,9 +31,13 @@ pub struct ComponentSettingCreate {
 
 /// Request body for updating a component setting
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ComponentSettingUpdate {
     pub value: Option<String>,
-    pub description: Option<String>,
+    /// `null` clears the description
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+    pub description: Patch<String>,
 }
 
 /// Grouped settings by category
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+/// Media type of RFC 7807 problem documents
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Stable, machine-readable reason a request failed
+///
+/// Clients should branch on the code rather than the message, which is meant
+/// for people and may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Malformed request: bad JSON, query string, path or header
+    BadRequest,
+    /// No valid session or token, or wrong credentials
+    Unauthenticated,
+    /// Authenticated, but the role or token scopes do not allow the action
+    Forbidden,
+    NotFound,
+    MethodNotAllowed,
+    /// The request conflicts with the record's current state
+    Conflict,
+    /// A record with the same unique value (name, email, key) already exists
+    AlreadyExists,
+    /// The `If-Match` version no longer matches the stored row
+    PreconditionFailed,
+    /// The request took longer than the server's time limit
+    RequestTimeout,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    /// Well-formed, but not acceptable as sent
+    Unprocessable,
+    /// The client sent too many requests; retry after the `Retry-After` header
+    RateLimited,
+    /// One or more fields failed validation; see `fields`
+    ValidationFailed,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BadRequest => "bad_request",
+            ErrorCode::Unauthenticated => "unauthenticated",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::MethodNotAllowed => "method_not_allowed",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::AlreadyExists => "already_exists",
+            ErrorCode::PreconditionFailed => "precondition_failed",
+            ErrorCode::RequestTimeout => "request_timeout",
+            ErrorCode::PayloadTooLarge => "payload_too_large",
+            ErrorCode::UnsupportedMediaType => "unsupported_media_type",
+            ErrorCode::Unprocessable => "unprocessable",
+            ErrorCode::ValidationFailed => "validation_failed",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::Internal => "internal",
+        }
+    }
+
+    /// HTTP status the server answers with for this code
+    pub fn status(&self) -> u16 {
+        match self {
+            ErrorCode::BadRequest => 400,
+            ErrorCode::Unauthenticated => 401,
+            ErrorCode::Forbidden => 403,
+            ErrorCode::NotFound => 404,
+            ErrorCode::MethodNotAllowed => 405,
+            ErrorCode::RequestTimeout => 408,
+            ErrorCode::Conflict | ErrorCode::AlreadyExists => 409,
+            ErrorCode::PreconditionFailed => 412,
+            ErrorCode::PayloadTooLarge => 413,
+            ErrorCode::UnsupportedMediaType => 415,
+            ErrorCode::Unprocessable | ErrorCode::ValidationFailed => 422,
+            ErrorCode::RateLimited => 429,
+            ErrorCode::Internal => 500,
+        }
+    }
+
+    /// Closest code for a bare HTTP status, for errors raised outside the handlers
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 => ErrorCode::Unauthenticated,
+            403 => ErrorCode::Forbidden,
+            404 => ErrorCode::NotFound,
+            405 => ErrorCode::MethodNotAllowed,
+            408 => ErrorCode::RequestTimeout,
+            409 => ErrorCode::Conflict,
+            412 => ErrorCode::PreconditionFailed,
+            413 => ErrorCode::PayloadTooLarge,
+            415 => ErrorCode::UnsupportedMediaType,
+            422 => ErrorCode::Unprocessable,
+            429 => ErrorCode::RateLimited,
+            500.. => ErrorCode::Internal,
+            _ => ErrorCode::BadRequest,
+        }
+    }
+
+    /// Short summary of the problem type, used as the problem document `title`
+    pub fn title(&self) -> &'static str {
+        match self {
+            ErrorCode::BadRequest => "Bad request",
+            ErrorCode::Unauthenticated => "Authentication required",
+            ErrorCode::Forbidden => "Forbidden",
+            ErrorCode::NotFound => "Not found",
+            ErrorCode::MethodNotAllowed => "Method not allowed",
+            ErrorCode::Conflict => "Conflict",
+            ErrorCode::AlreadyExists => "Already exists",
+            ErrorCode::PreconditionFailed => "Precondition failed",
+            ErrorCode::RequestTimeout => "Request timeout",
+            ErrorCode::PayloadTooLarge => "Payload too large",
+            ErrorCode::UnsupportedMediaType => "Unsupported media type",
+            ErrorCode::Unprocessable => "Unprocessable request",
+            ErrorCode::ValidationFailed => "Validation failed",
+            ErrorCode::RateLimited => "Too many requests",
+            ErrorCode::Internal => "Internal server error",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a single request field was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FieldError {
+    /// Field name as it appears in the request, e.g. `title` or `scopes`
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Body of every failed API response
+///
+/// ```json
+/// { "code": "already_exists", "error": "A theme with this name already exists",
+///   "request_id": "6f1c..." }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiError {
+    pub code: ErrorCode,
+    /// Human-readable message, safe to show to users
+    pub error: String,
+    /// Per-field problems; only set for `validation_failed`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// Id of the failed request, also sent as the `x-request-id` header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, error: impl Into<String>) -> Self {
+        Self {
+            code,
+            error: error.into(),
+            fields: Vec::new(),
+            request_id: None,
+        }
+    }
+
+    /// The same error as an RFC 7807 problem document
+    pub fn to_problem(&self) -> ProblemDetails {
+        ProblemDetails {
+            problem_type: format!("urn:atom-platform:error:{}", self.code),
+            title: self.code.title().to_string(),
+            status: self.code.status(),
+            detail: self.error.clone(),
+            code: self.code,
+            fields: self.fields.clone(),
+            request_id: self.request_id.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.error)?;
+        for (i, field) in self.fields.iter().enumerate() {
+            let separator = if i == 0 { ": " } else { "; " };
+            write!(f, "{}{} {}", separator, field.field, field.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// RFC 7807 `application/problem+json` rendering of an [`ApiError`]
+///
+/// Sent instead of the plain body when the request's `Accept` header asks for
+/// [`PROBLEM_JSON`]. `code`, `fields` and `request_id` are extension members.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    pub code: ErrorCode,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
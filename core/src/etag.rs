@@ -0,0 +1,16 @@
+/// Strong entity tag for a row version, e.g. `"3"`
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Row version carried by a strong entity tag
+///
+/// Weak tags (`W/"3"`) and anything else that is not a quoted version number
+/// yield `None`; `If-Match` only ever compares strong tags.
+pub fn parse_etag(tag: &str) -> Option<i32> {
+    tag.trim()
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .parse()
+        .ok()
+}
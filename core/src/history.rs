@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::Todo;
+
+/// Todo fields recorded in the audit history, in display order
+pub const TRACKED_TODO_FIELDS: &[&str] = &[
+    "parent_id",
+    "title",
+    "description",
+    "status",
+    "cancellation_reason",
+    "priority",
+    "due_at",
+    "tags",
+    "owner_id",
+    "assignee_id",
+    "deleted_at",
+];
+
+/// What happened to a todo in a history event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "todo_event_kind", rename_all = "snake_case"))]
+pub enum TodoEventKind {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl TodoEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoEventKind::Created => "created",
+            TodoEventKind::Updated => "updated",
+            TodoEventKind::Deleted => "deleted",
+            TodoEventKind::Restored => "restored",
+        }
+    }
+}
+
+impl std::fmt::Display for TodoEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// One field's value before and after a change, as JSON
+///
+/// `old` is `null` for created todos.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TodoFieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// A single entry in a todo's audit history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TodoEvent {
+    pub id: Uuid,
+    pub todo_id: Uuid,
+    pub kind: TodoEventKind,
+    pub changes: Vec<TodoFieldChange>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Field-level differences between two versions of a todo
+///
+/// Pass `None` as `before` for a newly created todo. Only
+/// [`TRACKED_TODO_FIELDS`] are compared, so moving a todo to or from the trash
+/// shows up as a `deleted_at` change.
+pub fn todo_changes(before: Option<&Todo>, after: Option<&Todo>) -> Vec<TodoFieldChange> {
+    let before = before.map(todo_fields).unwrap_or_default();
+    let after = after.map(todo_fields).unwrap_or_default();
+
+    TRACKED_TODO_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let old = before.get(*field).cloned().unwrap_or(Value::Null);
+            let new = after.get(*field).cloned().unwrap_or(Value::Null);
+            (old != new).then(|| TodoFieldChange {
+                field: field.to_string(),
+                old,
+                new,
+            })
+        })
+        .collect()
+}
+
+fn todo_fields(todo: &Todo) -> serde_json::Map<String, Value> {
+    match serde_json::to_value(todo) {
+        Ok(Value::Object(fields)) => fields,
+        _ => serde_json::Map::new(),
+    }
+}
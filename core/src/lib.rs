@@ -1,8 +1,41 @@
 pub mod todo;
+mod changes;
 mod component_settings;
+mod error;
+mod etag;
+mod history;
+mod pagination;
+mod patch;
+mod search;
 mod themes;
+mod tokens;
+mod trash;
+mod transitions;
+mod users;
+mod validation;
 
-pub use todo::{Todo, TodoCreate, TodoUpdate, TodoStatus};
+pub use todo::{
+    normalize_tags, Todo, TodoBulkAction, TodoBulkItemResult, TodoBulkOutcome, TodoBulkRequest,
+    TodoBulkResponse, TodoCreate, TodoListQuery, TodoMove, TodoPriority, TodoSortField, TodoStatus,
+    TodoUpdate, MAX_BULK_ITEMS,
+};
+pub use changes::{ChangeAction, ChangeEntity, ChangeEvent};
+pub use error::{ApiError, ErrorCode, FieldError, ProblemDetails, PROBLEM_JSON};
+pub use etag::{etag, parse_etag};
+pub use history::{todo_changes, TodoEvent, TodoEventKind, TodoFieldChange, TRACKED_TODO_FIELDS};
+pub use patch::Patch;
+pub use pagination::{Page, SortOrder, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+pub use search::{highlight_segments, TodoSearchHit, TodoSearchQuery, HIGHLIGHT_END, HIGHLIGHT_START};
+pub use transitions::TransitionError;
 pub use component_settings::{ComponentSetting, ComponentSettingCreate, ComponentSettingUpdate, SettingsGroup};
 pub use themes::{Theme, ThemeWithSettings, ThemeCreate, ThemeUpdate};
+pub use tokens::{ApiToken, ApiTokenCreate, ApiTokenCreated, API_TOKEN_PREFIX};
+pub use trash::DeletedQuery;
+pub use validation::{
+    field_error, is_css_color, is_css_length, setting_value_error, Validate, Validator,
+    MAX_DISPLAY_NAME_CHARS, MAX_EMAIL_CHARS, MAX_SETTING_DESCRIPTION_CHARS, MAX_SETTING_VALUE_CHARS,
+    MAX_STATUS_REASON_CHARS, MAX_TAGS, MAX_TAG_CHARS, MAX_THEME_NAME_CHARS,
+    MAX_TODO_DESCRIPTION_CHARS, MAX_TODO_TITLE_CHARS, MAX_TOKEN_NAME_CHARS, MIN_PASSWORD_CHARS,
+};
+pub use users::{Assignee, LoginRequest, LoginResponse, Permission, User, UserCreate, UserRole, UserRoleUpdate};
 
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// Page size used when a list request does not specify `limit`
+pub const DEFAULT_PAGE_LIMIT: i64 = 20;
+
+/// Largest `limit` a list request may ask for
+pub const MAX_PAGE_LIMIT: i64 = 100;
+
+/// Sort direction for list endpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// One page of a list result along with the total number of matching rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl<T> Page<T> {
+    /// Zero-based index of this page
+    pub fn page_index(&self) -> usize {
+        if self.limit <= 0 {
+            return 0;
+        }
+        (self.offset / self.limit) as usize
+    }
+
+    /// Number of pages needed to show every matching row
+    pub fn total_pages(&self) -> usize {
+        if self.limit <= 0 {
+            return 0;
+        }
+        ((self.total + self.limit - 1) / self.limit) as usize
+    }
+}
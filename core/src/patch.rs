@@ -0,0 +1,114 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// One field of a partial update: leave unchanged, clear, or set
+///
+/// A missing JSON field is [`Patch::Absent`], `null` is [`Patch::Null`] and any
+/// other value is [`Patch::Value`]. Declare patch fields with
+/// `#[serde(default, skip_serializing_if = "Patch::is_absent")]` so a missing
+/// field deserializes as `Absent` and round-trips as missing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// Patch that sets the field when `value` is `Some` and clears it otherwise
+    pub fn from_option(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        }
+    }
+
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    /// The new value, or `None` when the field is left unchanged or cleared
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_ref(&self) -> Patch<&T> {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(value),
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Patch<U> {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(f(value)),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            // Absent fields are normally skipped; serializing one anyway leaves the field cleared
+            Patch::Absent | Patch::Null => serializer.serialize_none(),
+            Patch::Value(value) => serializer.serialize_some(value),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(Patch::from_option)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Patch;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Update {
+        #[serde(default, skip_serializing_if = "Patch::is_absent")]
+        note: Patch<String>,
+    }
+
+    fn round_trip(value: serde_json::Value) -> (Update, serde_json::Value) {
+        let update: Update = serde_json::from_value(value).unwrap();
+        let json = serde_json::to_value(&update).unwrap();
+        (update, json)
+    }
+
+    #[test]
+    fn missing_field_is_absent_and_stays_missing() {
+        let (update, json) = round_trip(json!({}));
+        assert_eq!(update.note, Patch::Absent);
+        assert_eq!(json, json!({}));
+    }
+
+    #[test]
+    fn null_is_null_and_stays_null() {
+        let (update, json) = round_trip(json!({ "note": null }));
+        assert_eq!(update.note, Patch::Null);
+        assert_eq!(json, json!({ "note": null }));
+    }
+
+    #[test]
+    fn value_is_value_and_stays_the_same() {
+        let (update, json) = round_trip(json!({ "note": "milk" }));
+        assert_eq!(update.note, Patch::Value("milk".to_string()));
+        assert_eq!(json, json!({ "note": "milk" }));
+    }
+
+    #[test]
+    fn wrong_type_is_rejected() {
+        assert!(serde_json::from_value::<Update>(json!({ "note": 3 })).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Todo;
+
+/// Marker inserted before a matched term in search snippets
+pub const HIGHLIGHT_START: &str = "<mark>";
+
+/// Marker inserted after a matched term in search snippets
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+/// Query parameters for full-text todo search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct TodoSearchQuery {
+    /// Search terms; supports web-search syntax such as `"exact phrase"` and `-exclude`
+    pub q: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
+
+/// A todo matched by full-text search
+///
+/// Snippets wrap matched terms in [`HIGHLIGHT_START`] / [`HIGHLIGHT_END`]. They
+/// are not HTML-escaped, so render them with [`highlight_segments`] rather than
+/// as raw markup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TodoSearchHit {
+    pub todo: Todo,
+    pub rank: f32,
+    pub title_snippet: String,
+    pub description_snippet: Option<String>,
+}
+
+/// Splits a snippet into `(text, is_match)` segments
+pub fn highlight_segments(snippet: &str) -> Vec<(&str, bool)> {
+    let mut segments = Vec::new();
+    let mut rest = snippet;
+
+    while let Some(start) = rest.find(HIGHLIGHT_START) {
+        if start > 0 {
+            segments.push((&rest[..start], false));
+        }
+        let after_start = &rest[start + HIGHLIGHT_START.len()..];
+        match after_start.find(HIGHLIGHT_END) {
+            Some(end) => {
+                segments.push((&after_start[..end], true));
+                rest = &after_start[end + HIGHLIGHT_END.len()..];
+            }
+            None => {
+                rest = after_start;
+                break;
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        segments.push((rest, false));
+    }
+
+    segments
+}
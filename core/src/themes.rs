@@ -6,6 +6,7 @@ use crate::ComponentSetting;
 
 /// Theme
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
 pub struct Theme {
     pub id: Uuid,
@@ -13,10 +14,15 @@ pub struct Theme {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the theme was moved to the trash
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Incremented on every write; sent back as the `ETag`
+    pub version: i32,
 }
 
 /// Theme with its settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ThemeWithSettings {
     pub theme: Theme,
     pub settings: Vec<ComponentSetting>,
@@ -24,12 +30,14 @@ pub struct ThemeWithSettings {
 
 /// Request body for creating a theme
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ThemeCreate {
     pub name: String,
 }
 
 /// Request body for updating a theme
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ThemeUpdate {
     pub name: Option<String>,
     pub is_active: Option<bool>,
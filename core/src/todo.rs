@@ -2,11 +2,14 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{Patch, SortOrder};
+
 /// Status of a todo item
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
 #[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
-#[cfg_attr(feature = "sqlx", sqlx(type_name = "todo_status", rename_all = "lowercase"))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "todo_status", rename_all = "snake_case"))]
 pub enum TodoStatus {
     Pending,
     InProgress,
@@ -15,6 +18,13 @@ pub enum TodoStatus {
 }
 
 impl TodoStatus {
+    pub const ALL: [TodoStatus; 4] = [
+        TodoStatus::Pending,
+        TodoStatus::InProgress,
+        TodoStatus::Completed,
+        TodoStatus::Cancelled,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             TodoStatus::Pending => "pending",
@@ -31,31 +41,428 @@ impl std::fmt::Display for TodoStatus {
     }
 }
 
+// Lets `Vec<TodoStatus>` bind as a `todo_status[]` parameter
+#[cfg(feature = "sqlx")]
+impl sqlx::postgres::PgHasArrayType for TodoStatus {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_todo_status")
+    }
+}
+
+impl std::str::FromStr for TodoStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TodoStatus::Pending),
+            "in_progress" => Ok(TodoStatus::InProgress),
+            "completed" => Ok(TodoStatus::Completed),
+            "cancelled" => Ok(TodoStatus::Cancelled),
+            other => Err(format!("Unknown todo status: {}", other)),
+        }
+    }
+}
+
+/// Priority of a todo item, ordered from lowest to highest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "todo_priority", rename_all = "lowercase"))]
+pub enum TodoPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
+impl TodoPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoPriority::Low => "low",
+            TodoPriority::Medium => "medium",
+            TodoPriority::High => "high",
+            TodoPriority::Urgent => "urgent",
+        }
+    }
+}
+
+impl std::fmt::Display for TodoPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// Lets `Vec<TodoPriority>` bind as a `todo_priority[]` parameter
+#[cfg(feature = "sqlx")]
+impl sqlx::postgres::PgHasArrayType for TodoPriority {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("_todo_priority")
+    }
+}
+
+impl std::str::FromStr for TodoPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(TodoPriority::Low),
+            "medium" => Ok(TodoPriority::Medium),
+            "high" => Ok(TodoPriority::High),
+            "urgent" => Ok(TodoPriority::Urgent),
+            other => Err(format!("Unknown todo priority: {}", other)),
+        }
+    }
+}
+
 /// Complete todo item with all fields
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
 pub struct Todo {
     pub id: Uuid,
+    /// Todo this one is a subtask of
+    pub parent_id: Option<Uuid>,
     pub title: String,
     pub description: Option<String>,
     pub status: TodoStatus,
+    /// Why the todo was cancelled; set only when `status` is `Cancelled`
+    pub cancellation_reason: Option<String>,
+    pub priority: TodoPriority,
+    pub due_at: Option<DateTime<Utc>>,
+    /// Tag names, sorted alphabetically
+    pub tags: Vec<String>,
+    /// User who created the todo; `None` for todos created before accounts existed
+    pub owner_id: Option<Uuid>,
+    /// User the todo is assigned to
+    pub assignee_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the todo was moved to the trash
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Incremented on every write; sent back as the `ETag`
+    pub version: i32,
+    /// Live descendants at any depth, excluding cancelled ones
+    pub subtask_total: i64,
+    /// Completed descendants at any depth
+    pub subtask_completed: i64,
+}
+
+impl Todo {
+    /// Whether the todo is past its due date and still open
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        let open = matches!(self.status, TodoStatus::Pending | TodoStatus::InProgress);
+        open && self.due_at.is_some_and(|due| due < now)
+    }
+
+    /// Share of subtasks completed, from 0.0 to 1.0; `None` without subtasks
+    pub fn subtask_progress(&self) -> Option<f32> {
+        (self.subtask_total > 0)
+            .then(|| self.subtask_completed as f32 / self.subtask_total as f32)
+    }
 }
 
 /// Request body for creating a new todo
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TodoCreate {
     pub title: String,
     pub description: Option<String>,
     #[serde(default)]
     pub status: Option<TodoStatus>,
+    #[serde(default)]
+    pub priority: Option<TodoPriority>,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Creates the todo as a subtask of this todo
+    #[serde(default)]
+    pub parent_id: Option<Uuid>,
+    /// User to assign the todo to; the creator becomes its owner
+    #[serde(default)]
+    pub assignee_id: Option<Uuid>,
+}
+
+/// Request body for moving a todo under a different parent
+///
+/// `parent_id: null` makes the todo top-level. A todo cannot be moved under
+/// itself or one of its own subtasks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TodoMove {
+    pub parent_id: Option<Uuid>,
 }
 
 /// Request body for updating an existing todo
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `tags`, when present, replaces the todo's full tag set. Status changes must
+/// follow [`TodoStatus::allowed_transitions`], and cancelling requires a
+/// `status_reason`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TodoUpdate {
     pub title: Option<String>,
-    pub description: Option<String>,
+    /// `null` clears the description
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+    pub description: Patch<String>,
     pub status: Option<TodoStatus>,
+    #[serde(default)]
+    pub status_reason: Option<String>,
+    #[serde(default)]
+    pub priority: Option<TodoPriority>,
+    /// `null` clears the due date
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<DateTime<Utc>>))]
+    pub due_at: Patch<DateTime<Utc>>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Reassigns the todo; `null` unassigns it
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Uuid>))]
+    pub assignee_id: Patch<Uuid>,
+}
+
+impl TodoUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_absent()
+            && self.status.is_none()
+            && self.priority.is_none()
+            && self.due_at.is_absent()
+            && self.tags.is_none()
+            && self.assignee_id.is_absent()
+    }
+}
+
+/// Trims, lowercases and de-duplicates tag names, dropping empty ones
+pub fn normalize_tags<I, S>(tags: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut normalized: Vec<String> = tags
+        .into_iter()
+        .map(|tag| tag.as_ref().trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
 }
 
+/// Field used to order the todo list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TodoSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Title,
+    Status,
+    Priority,
+    DueAt,
+}
+
+impl TodoSortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoSortField::CreatedAt => "created_at",
+            TodoSortField::UpdatedAt => "updated_at",
+            TodoSortField::Title => "title",
+            TodoSortField::Status => "status",
+            TodoSortField::Priority => "priority",
+            TodoSortField::DueAt => "due_at",
+        }
+    }
+}
+
+/// Query parameters for listing todos
+///
+/// `status`, `priority` and `tag` are comma-separated lists on the wire, e.g.
+/// `?status=pending,in_progress&priority=high,urgent&tag=work&sort=due_at&order=asc`.
+/// A todo matches a list filter when it matches any of the listed values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct TodoListQuery {
+    #[serde(default, with = "comma_list", skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "openapi", param(value_type = Option<String>, example = "pending,in_progress"))]
+    pub status: Vec<TodoStatus>,
+    #[serde(default, with = "comma_list", skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "openapi", param(value_type = Option<String>, example = "high,urgent"))]
+    pub priority: Vec<TodoPriority>,
+    #[serde(default, with = "comma_list", skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "openapi", param(value_type = Option<String>, example = "work,home"))]
+    pub tag: Vec<String>,
+    /// Only todos due strictly before this instant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_before: Option<DateTime<Utc>>,
+    /// Only todos due at or after this instant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_after: Option<DateTime<Utc>>,
+    /// Case-insensitive substring matched against title and description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    #[serde(default)]
+    pub sort: TodoSortField,
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// Also list todos that are in the trash
+    #[serde(default)]
+    pub include_deleted: bool,
+    /// List only todos that are in the trash; implies `include_deleted`
+    #[serde(default)]
+    pub only_deleted: bool,
+    /// List only todos without a parent
+    #[serde(default)]
+    pub top_level: bool,
+    /// List only the caller's todos: those assigned to them, plus unassigned
+    /// ones they created
+    #[serde(default)]
+    pub mine: bool,
+}
+
+impl TodoListQuery {
+    /// Query string pairs for this query, omitting unset parameters
+    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+
+        if !self.status.is_empty() {
+            pairs.push(("status", comma_list::join(&self.status)));
+        }
+        if !self.priority.is_empty() {
+            pairs.push(("priority", comma_list::join(&self.priority)));
+        }
+        if !self.tag.is_empty() {
+            pairs.push(("tag", comma_list::join(&self.tag)));
+        }
+        if let Some(due_before) = self.due_before {
+            pairs.push(("due_before", due_before.to_rfc3339()));
+        }
+        if let Some(due_after) = self.due_after {
+            pairs.push(("due_after", due_after.to_rfc3339()));
+        }
+        if let Some(q) = self.q.as_ref().filter(|q| !q.trim().is_empty()) {
+            pairs.push(("q", q.clone()));
+        }
+        pairs.push(("sort", self.sort.as_str().to_string()));
+        pairs.push(("order", self.order.as_str().to_string()));
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            pairs.push(("offset", offset.to_string()));
+        }
+        if self.include_deleted {
+            pairs.push(("include_deleted", "true".to_string()));
+        }
+        if self.only_deleted {
+            pairs.push(("only_deleted", "true".to_string()));
+        }
+        if self.top_level {
+            pairs.push(("top_level", "true".to_string()));
+        }
+        if self.mine {
+            pairs.push(("mine", "true".to_string()));
+        }
+
+        pairs
+    }
+}
+
+/// (De)serializes a list of values as a single comma-separated string
+mod comma_list {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn join<T: Display>(values: &[T]) -> String {
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn serialize<S, T>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Display,
+    {
+        serializer.serialize_str(&join(values))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        let raw = String::deserialize(deserializer)?;
+        raw.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+/// Largest number of ids accepted by a single bulk request
+pub const MAX_BULK_ITEMS: usize = 500;
+
+/// Operation applied to every todo in a bulk request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TodoBulkAction {
+    SetStatus {
+        status: TodoStatus,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    Delete,
+    Edit(TodoUpdate),
+}
+
+/// Request body for `POST /api/todos/bulk`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TodoBulkRequest {
+    pub ids: Vec<Uuid>,
+    pub action: TodoBulkAction,
+}
+
+/// Result of a bulk action for a single todo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TodoBulkOutcome {
+    Updated { todo: Box<Todo> },
+    Deleted,
+    NotFound,
+    /// The change was refused for this todo, e.g. an illegal status transition
+    Rejected { error: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TodoBulkItemResult {
+    pub id: Uuid,
+    pub outcome: TodoBulkOutcome,
+}
+
+/// Response body for `POST /api/todos/bulk`, one result per requested id in
+/// request order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TodoBulkResponse {
+    pub results: Vec<TodoBulkItemResult>,
+}
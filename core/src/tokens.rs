@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Permission;
+
+/// Prefix of every personal access token, which tells them apart from session tokens
+pub const API_TOKEN_PREFIX: &str = "atom_pat_";
+
+/// A personal access token, without its secret
+///
+/// A request made with the token may do what both its `scopes` and its
+/// owner's role allow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub name: String,
+    /// First characters of the secret, to recognise the token in a list
+    pub prefix: String,
+    pub scopes: Vec<Permission>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// `None` for tokens that never expire
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Request body for `POST /api/tokens`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiTokenCreate {
+    pub name: String,
+    pub scopes: Vec<Permission>,
+    /// Lifetime in days; omit for a token that never expires
+    #[serde(default)]
+    pub expires_in_days: Option<u32>,
+}
+
+/// Response to `POST /api/tokens`; `secret` is shown this once and never stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiTokenCreated {
+    pub token: ApiToken,
+    pub secret: String,
+}
@@ -0,0 +1,168 @@
+use crate::TodoStatus;
+
+/// Why a status change was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    /// The state machine has no edge from `from` to `to`
+    NotAllowed { from: TodoStatus, to: TodoStatus },
+    /// Moving to `Cancelled` needs a non-empty reason
+    ReasonRequired,
+}
+
+impl std::fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransitionError::NotAllowed { from, to } if from.is_terminal() => write!(
+                f,
+                "Cannot change status from {} to {}: {} is a final status",
+                from, to, from
+            ),
+            TransitionError::NotAllowed { from, to } => {
+                write!(f, "Cannot change status from {} to {}", from, to)
+            }
+            TransitionError::ReasonRequired => {
+                write!(f, "A reason is required to cancel a todo")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+/// Todo lifecycle:
+///
+/// ```text
+/// Pending <-> InProgress --> Completed --> Pending (reopen)
+///    |            |
+///    +------------+--> Cancelled (terminal)
+/// ```
+///
+/// Pending may also go straight to Completed.
+impl TodoStatus {
+    /// Statuses this one may move to; empty for terminal statuses
+    pub fn allowed_transitions(&self) -> &'static [TodoStatus] {
+        match self {
+            TodoStatus::Pending => &[
+                TodoStatus::InProgress,
+                TodoStatus::Completed,
+                TodoStatus::Cancelled,
+            ],
+            TodoStatus::InProgress => &[
+                TodoStatus::Pending,
+                TodoStatus::Completed,
+                TodoStatus::Cancelled,
+            ],
+            TodoStatus::Completed => &[TodoStatus::Pending],
+            TodoStatus::Cancelled => &[],
+        }
+    }
+
+    /// Terminal statuses cannot be left once reached
+    pub fn is_terminal(&self) -> bool {
+        self.allowed_transitions().is_empty()
+    }
+
+    /// Whether a reason must accompany a change into this status
+    pub fn requires_reason(&self) -> bool {
+        matches!(self, TodoStatus::Cancelled)
+    }
+
+    /// Whether `next` is reachable from this status; staying put is always allowed
+    pub fn can_transition_to(&self, next: TodoStatus) -> bool {
+        *self == next || self.allowed_transitions().contains(&next)
+    }
+
+    /// Validates a change to `next`, including the reason required for cancellation
+    pub fn check_transition(
+        &self,
+        next: TodoStatus,
+        reason: Option<&str>,
+    ) -> Result<(), TransitionError> {
+        if *self == next {
+            return Ok(());
+        }
+        if !self.can_transition_to(next) {
+            return Err(TransitionError::NotAllowed {
+                from: *self,
+                to: next,
+            });
+        }
+        let has_reason = reason.is_some_and(|r| !r.trim().is_empty());
+        if next.requires_reason() && !has_reason {
+            return Err(TransitionError::ReasonRequired);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransitionError;
+    use crate::TodoStatus::{self, Cancelled, Completed, InProgress, Pending};
+
+    /// Every `(from, to)` pair with whether the move is allowed, written out
+    /// rather than derived from `allowed_transitions`
+    const MOVES: [(TodoStatus, TodoStatus, bool); 16] = [
+        (Pending, Pending, true),
+        (Pending, InProgress, true),
+        (Pending, Completed, true),
+        (Pending, Cancelled, true),
+        (InProgress, Pending, true),
+        (InProgress, InProgress, true),
+        (InProgress, Completed, true),
+        (InProgress, Cancelled, true),
+        (Completed, Pending, true),
+        (Completed, InProgress, false),
+        (Completed, Completed, true),
+        (Completed, Cancelled, false),
+        (Cancelled, Pending, false),
+        (Cancelled, InProgress, false),
+        (Cancelled, Completed, false),
+        (Cancelled, Cancelled, true),
+    ];
+
+    #[test]
+    fn every_pair_is_allowed_or_rejected() {
+        for (from, to, allowed) in MOVES {
+            assert_eq!(from.can_transition_to(to), allowed, "{} -> {}", from, to);
+            let result = from.check_transition(to, Some("no longer needed"));
+            if allowed {
+                assert_eq!(result, Ok(()), "{} -> {}", from, to);
+            } else {
+                assert_eq!(result, Err(TransitionError::NotAllowed { from, to }));
+            }
+        }
+    }
+
+    #[test]
+    fn only_cancelled_is_terminal() {
+        for status in [Pending, InProgress, Completed, Cancelled] {
+            assert_eq!(status.is_terminal(), status == Cancelled, "{}", status);
+        }
+        let error = Cancelled.check_transition(Pending, None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Cannot change status from cancelled to pending: cancelled is a final status"
+        );
+    }
+
+    #[test]
+    fn cancelling_needs_a_reason() {
+        for from in [Pending, InProgress] {
+            for reason in [None, Some(""), Some("   ")] {
+                assert_eq!(
+                    from.check_transition(Cancelled, reason),
+                    Err(TransitionError::ReasonRequired),
+                    "{} with {:?}",
+                    from,
+                    reason
+                );
+            }
+            assert_eq!(from.check_transition(Cancelled, Some("duplicate")), Ok(()));
+        }
+        // Staying cancelled is not a new cancellation
+        assert_eq!(Cancelled.check_transition(Cancelled, None), Ok(()));
+        // Other moves never need one
+        assert_eq!(Pending.check_transition(Completed, None), Ok(()));
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Query parameters for endpoints that hide soft-deleted rows by default
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct DeletedQuery {
+    /// Also return rows that are in the trash
+    #[serde(default)]
+    pub include_deleted: bool,
+}
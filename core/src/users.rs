@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An account that can sign in to the platform
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct User {
+    pub id: Uuid,
+    pub email: String,
+    pub display_name: String,
+    pub role: UserRole,
+    pub created_at: DateTime<Utc>,
+}
+
+impl User {
+    pub fn can(&self, permission: Permission) -> bool {
+        self.role.has(permission)
+    }
+}
+
+/// The part of an account every signed-in user may see, for showing and picking assignees
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Assignee {
+    pub id: Uuid,
+    pub display_name: String,
+}
+
+/// What a user may do, ordered from least to most trusted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "user_role", rename_all = "lowercase"))]
+pub enum UserRole {
+    /// Read-only access
+    #[default]
+    Viewer,
+    /// Can create and change individual todos
+    Editor,
+    /// Everything, including themes, settings, bulk actions and accounts
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Viewer => "viewer",
+            UserRole::Editor => "editor",
+            UserRole::Admin => "admin",
+        }
+    }
+
+    /// Whether this role grants `permission`
+    pub fn has(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::Read => true,
+            Permission::EditTodos => *self >= UserRole::Editor,
+            Permission::ManageTodos | Permission::ManageSettings | Permission::ManageUsers => {
+                *self == UserRole::Admin
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An action guarded by role, checked on the server and used by the client to hide UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// List and view todos, themes and settings
+    Read,
+    /// Create, edit, move and delete individual todos
+    EditTodos,
+    /// Bulk todo actions and restoring from the trash
+    ManageTodos,
+    /// Create, activate and delete themes, and change settings
+    ManageSettings,
+    /// Create accounts and change roles
+    ManageUsers,
+}
+
+impl Permission {
+    /// Every permission, in order of increasing trust
+    pub const ALL: [Permission; 5] = [
+        Permission::Read,
+        Permission::EditTodos,
+        Permission::ManageTodos,
+        Permission::ManageSettings,
+        Permission::ManageUsers,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Read => "read",
+            Permission::EditTodos => "edit_todos",
+            Permission::ManageTodos => "manage_todos",
+            Permission::ManageSettings => "manage_settings",
+            Permission::ManageUsers => "manage_users",
+        }
+    }
+
+    /// Lowest role that has this permission
+    pub fn min_role(&self) -> UserRole {
+        [UserRole::Viewer, UserRole::Editor, UserRole::Admin]
+            .into_iter()
+            .find(|role| role.has(*self))
+            .unwrap_or(UserRole::Admin)
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == s)
+            .ok_or_else(|| format!("Unknown permission: {}", s))
+    }
+}
+
+/// Request body for `POST /api/users`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UserCreate {
+    pub email: String,
+    pub display_name: String,
+    pub password: String,
+    #[serde(default)]
+    pub role: UserRole,
+}
+
+/// Request body for `PUT /api/users/:id/role`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UserRoleUpdate {
+    pub role: UserRole,
+}
+
+/// Request body for `POST /api/auth/login`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// A new session returned by a successful login
+///
+/// `token` is also set as an HttpOnly cookie; clients that cannot rely on
+/// cookies send it as `Authorization: Bearer <token>` instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginResponse {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: User,
+}
@@ -0,0 +1,470 @@
+//! Validation rules for request bodies
+//!
+//! The server runs them before a handler sees the body and answers `422` with
+//! the collected [`FieldError`]s; the client runs the same rules to flag bad
+//! input before sending it.
+
+use crate::{
+    normalize_tags, ApiTokenCreate, ComponentSettingUpdate, FieldError, Patch, ThemeCreate,
+    ThemeUpdate, TodoBulkAction, TodoBulkRequest, TodoCreate, TodoUpdate, UserCreate,
+    MAX_BULK_ITEMS,
+};
+
+pub const MAX_TODO_TITLE_CHARS: usize = 200;
+pub const MAX_TODO_DESCRIPTION_CHARS: usize = 10_000;
+pub const MAX_STATUS_REASON_CHARS: usize = 500;
+pub const MAX_TAGS: usize = 20;
+pub const MAX_TAG_CHARS: usize = 40;
+pub const MAX_THEME_NAME_CHARS: usize = 60;
+pub const MAX_SETTING_VALUE_CHARS: usize = 200;
+pub const MAX_SETTING_DESCRIPTION_CHARS: usize = 500;
+pub const MAX_DISPLAY_NAME_CHARS: usize = 100;
+pub const MAX_EMAIL_CHARS: usize = 254;
+pub const MIN_PASSWORD_CHARS: usize = 8;
+pub const MAX_TOKEN_NAME_CHARS: usize = 100;
+
+/// A request body that can check itself
+pub trait Validate {
+    /// Every problem with the body, or `Ok` when there is none
+    fn validate(&self) -> Result<(), Vec<FieldError>>;
+}
+
+/// Collects field errors, keeping only the first one per field
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `message` against `field` unless `ok`
+    pub fn check(&mut self, field: &str, ok: bool, message: impl Into<String>) -> &mut Self {
+        if !ok && !self.errors.iter().any(|error| error.field == field) {
+            self.errors.push(FieldError::new(field, message));
+        }
+        self
+    }
+
+    /// The value must contain something other than whitespace
+    pub fn required(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(field, !value.trim().is_empty(), "is required")
+    }
+
+    pub fn max_chars(&mut self, field: &str, value: &str, max: usize) -> &mut Self {
+        self.check(
+            field,
+            value.chars().count() <= max,
+            format!("must be at most {} characters", max),
+        )
+    }
+
+    pub fn min_chars(&mut self, field: &str, value: &str, min: usize) -> &mut Self {
+        self.check(
+            field,
+            value.chars().count() >= min,
+            format!("must be at least {} characters", min),
+        )
+    }
+
+    /// Records a problem reported by a format check such as [`setting_value_error`]
+    pub fn format(&mut self, field: &str, problem: Option<String>) -> &mut Self {
+        match problem {
+            Some(message) => self.check(field, false, message),
+            None => self,
+        }
+    }
+
+    /// Adds errors from a nested body, prefixing their fields with `prefix.`
+    pub fn nested(&mut self, prefix: &str, result: Result<(), Vec<FieldError>>) -> &mut Self {
+        for error in result.err().unwrap_or_default() {
+            let field = format!("{}.{}", prefix, error.field);
+            self.check(&field, false, error.message);
+        }
+        self
+    }
+
+    pub fn finish(&mut self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+}
+
+/// The first error recorded for `field`, for showing next to a form input
+pub fn field_error<'a>(errors: &'a [FieldError], field: &str) -> Option<&'a str> {
+    errors
+        .iter()
+        .find(|error| error.field == field)
+        .map(|error| error.message.as_str())
+}
+
+fn check_tags(v: &mut Validator, tags: &[String]) {
+    let tags = normalize_tags(tags);
+    v.check(
+        "tags",
+        tags.len() <= MAX_TAGS,
+        format!("must have at most {} tags", MAX_TAGS),
+    );
+    v.check(
+        "tags",
+        tags.iter().all(|tag| tag.chars().count() <= MAX_TAG_CHARS),
+        format!("each tag must be at most {} characters", MAX_TAG_CHARS),
+    );
+}
+
+impl Validate for TodoCreate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut v = Validator::new();
+        v.required("title", &self.title)
+            .max_chars("title", &self.title, MAX_TODO_TITLE_CHARS);
+        if let Some(description) = &self.description {
+            v.max_chars("description", description, MAX_TODO_DESCRIPTION_CHARS);
+        }
+        check_tags(&mut v, &self.tags);
+        v.finish()
+    }
+}
+
+impl Validate for TodoUpdate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut v = Validator::new();
+        if let Some(title) = &self.title {
+            v.required("title", title)
+                .max_chars("title", title, MAX_TODO_TITLE_CHARS);
+        }
+        if let Patch::Value(description) = &self.description {
+            v.max_chars("description", description, MAX_TODO_DESCRIPTION_CHARS);
+        }
+        if let Some(reason) = &self.status_reason {
+            v.max_chars("status_reason", reason, MAX_STATUS_REASON_CHARS);
+        }
+        if let Some(tags) = &self.tags {
+            check_tags(&mut v, tags);
+        }
+        v.finish()
+    }
+}
+
+impl Validate for TodoBulkRequest {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut v = Validator::new();
+        v.check("ids", !self.ids.is_empty(), "must contain at least one id")
+            .check(
+                "ids",
+                self.ids.len() <= MAX_BULK_ITEMS,
+                format!("must contain at most {} ids", MAX_BULK_ITEMS),
+            );
+        match &self.action {
+            TodoBulkAction::SetStatus {
+                reason: Some(reason),
+                ..
+            } => {
+                v.max_chars("action.reason", reason, MAX_STATUS_REASON_CHARS);
+            }
+            TodoBulkAction::Edit(update) => {
+                v.nested("action", update.validate());
+            }
+            _ => {}
+        }
+        v.finish()
+    }
+}
+
+impl Validate for ThemeCreate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        Validator::new()
+            .required("name", &self.name)
+            .max_chars("name", &self.name, MAX_THEME_NAME_CHARS)
+            .finish()
+    }
+}
+
+impl Validate for ThemeUpdate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut v = Validator::new();
+        if let Some(name) = &self.name {
+            v.required("name", name)
+                .max_chars("name", name, MAX_THEME_NAME_CHARS);
+        }
+        v.finish()
+    }
+}
+
+/// Checks only lengths; the value's format depends on the stored category and
+/// is checked with [`setting_value_error`] once the setting is loaded
+impl Validate for ComponentSettingUpdate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut v = Validator::new();
+        if let Some(value) = &self.value {
+            v.max_chars("value", value, MAX_SETTING_VALUE_CHARS);
+        }
+        if let Patch::Value(description) = &self.description {
+            v.max_chars("description", description, MAX_SETTING_DESCRIPTION_CHARS);
+        }
+        v.finish()
+    }
+}
+
+impl Validate for UserCreate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let email = self.email.trim();
+        Validator::new()
+            .required("email", email)
+            .max_chars("email", email, MAX_EMAIL_CHARS)
+            .check("email", is_plausible_email(email), "must be an email address")
+            .required("display_name", &self.display_name)
+            .max_chars("display_name", self.display_name.trim(), MAX_DISPLAY_NAME_CHARS)
+            .required("password", &self.password)
+            .min_chars("password", &self.password, MIN_PASSWORD_CHARS)
+            .finish()
+    }
+}
+
+impl Validate for ApiTokenCreate {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        Validator::new()
+            .required("name", &self.name)
+            .max_chars("name", self.name.trim(), MAX_TOKEN_NAME_CHARS)
+            .check("scopes", !self.scopes.is_empty(), "must contain at least one scope")
+            .check(
+                "expires_in_days",
+                self.expires_in_days != Some(0),
+                "must be at least 1",
+            )
+            .finish()
+    }
+}
+
+/// Something, an `@`, then a domain with a dot; delivery is the real test
+fn is_plausible_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Why `value` is not acceptable for a setting in `category`, if it is not
+///
+/// | Category | Accepted values |
+/// |----------|-----------------|
+/// | `colors` | `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`/`rgba()`/`hsl()`/`hsla()` |
+/// | `spacing`, `borders`, `typography` | `0` or a non-negative length such as `1.5rem` or `12px` |
+/// | `theme` | `light` or `dark` |
+///
+/// Other categories only need a non-empty value.
+pub fn setting_value_error(category: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Some("is required".to_string());
+    }
+    let ok = match category {
+        "colors" => is_css_color(value),
+        "spacing" | "borders" | "typography" => is_css_length(value),
+        "theme" => matches!(value, "light" | "dark"),
+        _ => true,
+    };
+    if ok {
+        return None;
+    }
+    Some(match category {
+        "colors" => "must be a CSS color such as #3b82f6 or rgb(59, 130, 246)".to_string(),
+        "theme" => "must be light or dark".to_string(),
+        _ => "must be a CSS length such as 1rem or 12px".to_string(),
+    })
+}
+
+/// Hex colors and the `rgb`/`hsl` functional notations
+pub fn is_css_color(value: &str) -> bool {
+    if let Some(hex) = value.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    let lower = value.to_ascii_lowercase();
+    let Some((function, rest)) = lower.split_once('(') else {
+        return false;
+    };
+    let Some(arguments) = rest.strip_suffix(')') else {
+        return false;
+    };
+    let arguments: Vec<&str> = arguments
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+    matches!(function, "rgb" | "rgba" | "hsl" | "hsla")
+        && matches!(arguments.len(), 3 | 4)
+        && arguments.iter().all(|argument| {
+            let number = argument
+                .strip_suffix('%')
+                .or_else(|| argument.strip_suffix("deg"))
+                .unwrap_or(argument);
+            number.parse::<f64>().is_ok_and(|n| n.is_finite() && n >= 0.0)
+        })
+}
+
+/// Units accepted by [`is_css_length`]
+const CSS_LENGTH_UNITS: [&str; 8] = ["px", "rem", "em", "%", "vh", "vw", "pt", "ch"];
+
+/// `0`, or a non-negative number followed by one of [`CSS_LENGTH_UNITS`]
+pub fn is_css_length(value: &str) -> bool {
+    if value == "0" {
+        return true;
+    }
+    CSS_LENGTH_UNITS.iter().any(|unit| {
+        value.strip_suffix(unit).is_some_and(|number| {
+            !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit() || c == '.')
+                && number.parse::<f64>().is_ok()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_css_color, is_css_length, setting_value_error};
+
+    /// Migrations whose rows seed theme and component settings
+    const SEEDS: [&str; 3] = [
+        include_str!("../../platform/api/migrations/20240103000000_create_component_settings.sql"),
+        include_str!("../../platform/api/migrations/20240104000000_add_theme_mode.sql"),
+        include_str!("../../platform/api/migrations/20240105000000_create_themes_system.sql"),
+    ];
+
+    #[test]
+    fn css_colors() {
+        let cases = [
+            ("#fff", true),
+            ("#ffff", true),
+            ("#3b82f6", true),
+            ("#3B82F6", true),
+            ("#3b82f680", true),
+            ("#", false),
+            ("#f", false),
+            ("#ff", false),
+            ("#fffff", false),
+            ("#fffffff", false),
+            ("#fffffffff", false),
+            ("#ggg", false),
+            ("3b82f6", false),
+            ("rgb(59, 130, 246)", true),
+            ("rgba(59, 130, 246, 0.5)", true),
+            ("RGB(59,130,246)", true),
+            ("rgb(59 130 246 / 50%)", true),
+            ("hsl(217, 91%, 60%)", true),
+            ("hsla(217deg, 91%, 60%, 0.5)", true),
+            ("rgb(59, 130)", false),
+            ("rgb(1, 2, 3, 4, 5)", false),
+            ("rgb(-1, 130, 246)", false),
+            ("rgb(59, 130, 246", false),
+            ("rgb(red, 130, 246)", false),
+            ("cmyk(0, 0, 0, 0)", false),
+            ("blue", false),
+            ("", false),
+        ];
+        for (value, valid) in cases {
+            assert_eq!(is_css_color(value), valid, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn css_lengths() {
+        let cases = [
+            ("0", true),
+            ("0px", true),
+            ("12px", true),
+            ("1rem", true),
+            ("1.5em", true),
+            ("0.875rem", true),
+            ("50%", true),
+            ("100vh", true),
+            ("100vw", true),
+            ("12pt", true),
+            ("60ch", true),
+            ("-1px", false),
+            ("-0.5rem", false),
+            ("+1px", false),
+            ("1e2px", false),
+            ("1.2.3px", false),
+            (".px", false),
+            ("px", false),
+            ("12", false),
+            ("12 px", false),
+            ("12cm", false),
+            ("", false),
+        ];
+        for (value, valid) in cases {
+            assert_eq!(is_css_length(value), valid, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn setting_values_by_category() {
+        let cases = [
+            ("colors", "#3b82f6", true),
+            ("colors", "1rem", false),
+            ("spacing", "1rem", true),
+            ("spacing", "#3b82f6", false),
+            ("borders", "0", true),
+            ("borders", "-2px", false),
+            ("typography", "1.125rem", true),
+            ("typography", "large", false),
+            ("theme", "light", true),
+            ("theme", "dark", true),
+            ("theme", "Dark", false),
+            ("custom", "anything", true),
+            // Surrounding whitespace is ignored
+            ("colors", "  #fff ", true),
+        ];
+        for (category, value, valid) in cases {
+            assert_eq!(
+                setting_value_error(category, value).is_none(),
+                valid,
+                "{} {:?}",
+                category,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn empty_values_are_required_in_every_category() {
+        for category in ["colors", "spacing", "borders", "typography", "theme", "custom"] {
+            for value in ["", "   "] {
+                assert_eq!(
+                    setting_value_error(category, value).as_deref(),
+                    Some("is required"),
+                    "{} {:?}",
+                    category,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_settings_are_valid() {
+        let mut seeded = 0;
+        for sql in SEEDS {
+            for line in sql.lines().map(str::trim).filter(|line| line.starts_with('(')) {
+                // Rows read `([theme_id, ]'key', 'value', 'category', 'description')`
+                let quoted: Vec<&str> = line.split('\'').skip(1).step_by(2).collect();
+                let [key, value, category, ..] = quoted[..] else {
+                    continue;
+                };
+                assert_eq!(setting_value_error(category, value), None, "{} = {:?}", key, value);
+                seeded += 1;
+            }
+        }
+        assert_eq!(seeded, 33);
+    }
+}
@@ -32,6 +32,10 @@ case "$MODE" in
         echo -e "${YELLOW}(Press Ctrl+C to exit)${NC}"
         echo ""
         tail -f "$LOG_DIR/api.log" | while read line; do
+            # LOG_FORMAT=json lines: show time, level, message and request id
+            if [[ "$line" == "{"* ]] && command -v jq >/dev/null; then
+                line=$(echo "$line" | jq -r '[.timestamp, .level, .fields.message, (.span.request_id // empty)] | join(" ")' 2>/dev/null || echo "$line")
+            fi
             if echo "$line" | grep -qi "error"; then
                 echo -e "${RED}$line${NC}"
             elif echo "$line" | grep -qi "warn"; then
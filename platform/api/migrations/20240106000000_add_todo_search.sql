@@ -0,0 +1,9 @@
+-- Add a generated full-text search vector over title (weight A) and description (weight B)
+ALTER TABLE todos
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(description, '')), 'B')
+    ) STORED;
+
+-- Create GIN index for fast full-text matching
+CREATE INDEX idx_todos_search_vector ON todos USING GIN (search_vector);
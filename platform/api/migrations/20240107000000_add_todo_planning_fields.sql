@@ -0,0 +1,26 @@
+-- Create todo_priority enum type (declaration order is the sort order)
+CREATE TYPE todo_priority AS ENUM ('low', 'medium', 'high', 'urgent');
+
+-- Add due date and priority to todos
+ALTER TABLE todos
+    ADD COLUMN due_at TIMESTAMPTZ,
+    ADD COLUMN priority todo_priority NOT NULL DEFAULT 'medium';
+
+CREATE INDEX idx_todos_due_at ON todos(due_at);
+CREATE INDEX idx_todos_priority ON todos(priority);
+
+-- Create tags table (names are stored normalized: trimmed and lowercase)
+CREATE TABLE IF NOT EXISTS tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create todo_tags join table
+CREATE TABLE IF NOT EXISTS todo_tags (
+    todo_id UUID NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (todo_id, tag_id)
+);
+
+CREATE INDEX idx_todo_tags_tag_id ON todo_tags(tag_id);
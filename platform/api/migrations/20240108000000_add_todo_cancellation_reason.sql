@@ -0,0 +1,2 @@
+-- Record why a todo was cancelled (required by the API when cancelling)
+ALTER TABLE todos ADD COLUMN cancellation_reason TEXT;
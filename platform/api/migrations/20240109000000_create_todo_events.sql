@@ -0,0 +1,14 @@
+-- Create todo_event_kind enum type
+CREATE TYPE todo_event_kind AS ENUM ('created', 'updated', 'deleted');
+
+-- Audit history of todo changes; kept after the todo itself is deleted
+CREATE TABLE IF NOT EXISTS todo_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    todo_id UUID NOT NULL,
+    kind todo_event_kind NOT NULL,
+    -- Array of {"field", "old", "new"} objects
+    changes JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_todo_events_todo_id ON todo_events(todo_id, created_at);
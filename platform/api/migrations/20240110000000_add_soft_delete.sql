@@ -0,0 +1,14 @@
+-- Soft delete: rows are hidden once deleted_at is set and purged after the
+-- configured retention period
+ALTER TABLE todos ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE themes ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_todos_deleted_at ON todos(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_themes_deleted_at ON themes(deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Deleted themes should not block reusing their name
+ALTER TABLE themes DROP CONSTRAINT themes_name_key;
+CREATE UNIQUE INDEX idx_themes_name_live ON themes(name) WHERE deleted_at IS NULL;
+
+-- History entry for todos brought back from the trash
+ALTER TYPE todo_event_kind ADD VALUE 'restored';
@@ -0,0 +1,25 @@
+-- Subtasks: a todo may belong to a parent todo. Purging a parent purges its
+-- subtree, which soft delete has already moved to the trash with it.
+ALTER TABLE todos ADD COLUMN parent_id UUID REFERENCES todos(id) ON DELETE CASCADE;
+ALTER TABLE todos ADD CONSTRAINT todos_parent_not_self CHECK (parent_id <> id);
+
+CREATE INDEX idx_todos_parent_id ON todos(parent_id);
+
+-- Roll-up progress over every live descendant of a todo. Cancelled subtasks
+-- are left out of the total so they do not hold progress back.
+CREATE OR REPLACE FUNCTION todo_subtask_progress(root UUID)
+RETURNS TABLE (total BIGINT, completed BIGINT)
+LANGUAGE sql STABLE AS $$
+    WITH RECURSIVE descendants AS (
+        SELECT id, status FROM todos
+        WHERE parent_id = root AND deleted_at IS NULL
+        UNION
+        SELECT t.id, t.status FROM todos t
+        JOIN descendants d ON t.parent_id = d.id
+        WHERE t.deleted_at IS NULL
+    )
+    SELECT
+        COUNT(*) FILTER (WHERE status <> 'cancelled'),
+        COUNT(*) FILTER (WHERE status = 'completed')
+    FROM descendants
+$$;
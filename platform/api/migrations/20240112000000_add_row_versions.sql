@@ -0,0 +1,24 @@
+-- Row versions for optimistic concurrency. Every UPDATE bumps the version, so
+-- a client's If-Match header can be compared against the row it last read.
+CREATE OR REPLACE FUNCTION bump_row_version()
+RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$;
+
+ALTER TABLE todos ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE themes ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE theme_settings ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE component_settings ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+CREATE TRIGGER todos_bump_version BEFORE UPDATE ON todos
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+CREATE TRIGGER themes_bump_version BEFORE UPDATE ON themes
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+CREATE TRIGGER theme_settings_bump_version BEFORE UPDATE ON theme_settings
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+CREATE TRIGGER component_settings_bump_version BEFORE UPDATE ON component_settings
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
@@ -0,0 +1,8 @@
+-- Roles in increasing order of trust
+CREATE TYPE user_role AS ENUM ('viewer', 'editor', 'admin');
+
+ALTER TABLE users
+    ADD COLUMN role user_role NOT NULL DEFAULT 'viewer';
+
+-- Accounts created before roles existed had full access; keep it that way
+UPDATE users SET role = 'admin';
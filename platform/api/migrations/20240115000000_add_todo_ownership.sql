@@ -0,0 +1,8 @@
+-- Who created each todo and who is working on it. Todos created before
+-- accounts existed have no owner.
+ALTER TABLE todos
+    ADD COLUMN owner_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN assignee_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_todos_owner_id ON todos(owner_id);
+CREATE INDEX idx_todos_assignee_id ON todos(assignee_id);
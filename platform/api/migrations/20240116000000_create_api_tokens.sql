@@ -0,0 +1,17 @@
+-- Personal access tokens for scripts and CI. As with sessions, only a
+-- SHA-256 hash of the secret is stored.
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- Leading characters of the secret, shown to help tell tokens apart
+    prefix TEXT NOT NULL,
+    token_hash BYTEA NOT NULL UNIQUE,
+    -- Permission names, e.g. 'read' or 'edit_todos'
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_api_tokens_user_id ON api_tokens(user_id);
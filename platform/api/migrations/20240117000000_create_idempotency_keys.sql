@@ -0,0 +1,21 @@
+-- Responses to POST requests sent with an Idempotency-Key header, replayed
+-- when the client retries. Keys are per user, so two users may pick the same.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    -- SHA-256 of the method, path and body; a key only replays the same request
+    request_hash BYTEA NOT NULL,
+    -- The response columns stay NULL while the first request is running
+    response_status SMALLINT,
+    -- Content-Type, ETag and Location of the response, by lower-case name
+    response_headers JSONB,
+    response_body BYTEA,
+    -- Reset whenever a request takes the key over. A claim still without a
+    -- response after the request timeout was abandoned, and the next retry may
+    -- take it.
+    claimed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, key)
+);
+
+CREATE INDEX idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
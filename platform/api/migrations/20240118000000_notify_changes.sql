@@ -0,0 +1,44 @@
+-- Publishes every change to todos, themes and theme settings on the
+-- atom_changes channel for the live change feed. The payload is a
+-- platform_core::ChangeEvent; the trigger argument names the entity.
+CREATE OR REPLACE FUNCTION notify_change()
+RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+DECLARE
+    changed JSONB;
+    action TEXT;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        changed := to_jsonb(OLD);
+        action := 'deleted';
+    ELSE
+        changed := to_jsonb(NEW);
+        action := CASE TG_OP WHEN 'INSERT' THEN 'created' ELSE 'updated' END;
+        -- Moving into or out of the trash reads as a delete or create
+        IF TG_OP = 'UPDATE' AND changed ? 'deleted_at' THEN
+            IF to_jsonb(OLD)->>'deleted_at' IS NULL AND changed->>'deleted_at' IS NOT NULL THEN
+                action := 'deleted';
+            ELSIF to_jsonb(OLD)->>'deleted_at' IS NOT NULL AND changed->>'deleted_at' IS NULL THEN
+                action := 'created';
+            END IF;
+        END IF;
+    END IF;
+
+    PERFORM pg_notify('atom_changes', jsonb_build_object(
+        'entity', TG_ARGV[0],
+        'action', action,
+        'id', changed->'id',
+        'theme_id', changed->'theme_id',
+        'parent_id', changed->'parent_id',
+        'version', changed->'version'
+    )::text);
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER todos_notify_change AFTER INSERT OR UPDATE OR DELETE ON todos
+    FOR EACH ROW EXECUTE FUNCTION notify_change('todo');
+CREATE TRIGGER themes_notify_change AFTER INSERT OR UPDATE OR DELETE ON themes
+    FOR EACH ROW EXECUTE FUNCTION notify_change('theme');
+CREATE TRIGGER theme_settings_notify_change AFTER INSERT OR UPDATE OR DELETE ON theme_settings
+    FOR EACH ROW EXECUTE FUNCTION notify_change('theme_setting');
@@ -0,0 +1,341 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts, Request},
+    http::{header, request::Parts, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use platform_core::{Permission, User, UserRole, API_TOKEN_PREFIX};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::OnceLock,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+use crate::{
+    db::Database,
+    error::{AppError, Result},
+};
+
+/// Cookie holding the session token
+pub const SESSION_COOKIE: &str = "atom_session";
+
+/// The signed-in user making the request
+///
+/// Extracting it fails with `401 Unauthorized` unless the request carries a
+/// live session token or personal access token, either as the
+/// [`SESSION_COOKIE`] cookie or as `Authorization: Bearer <token>`. The
+/// matching [`Credential`] is left in the request extensions.
+#[derive(Debug, Clone)]
+pub struct CurrentUser(pub User);
+
+/// Left in the request extensions once its token has been looked up and
+/// refused, so later extractions refuse it without asking the database again
+#[derive(Debug, Clone, Copy)]
+struct Rejected;
+
+/// How the current request was authenticated
+#[derive(Debug, Clone)]
+pub enum Credential {
+    /// A login session; limited only by the user's role
+    Session,
+    /// A personal access token, further limited to its scopes
+    ApiToken { id: Uuid, scopes: Vec<Permission> },
+}
+
+impl Credential {
+    /// Whether the credential itself permits `permission`, regardless of role
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            Credential::Session => true,
+            Credential::ApiToken { scopes, .. } => scopes.contains(&permission),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    Database: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        // The route layer and the handler may both ask for the user
+        if let Some(user) = parts.extensions.get::<CurrentUser>() {
+            return Ok(user.clone());
+        }
+        if parts.extensions.get::<Rejected>().is_some() {
+            return Err(authentication_required());
+        }
+
+        let token = session_token(&parts.headers).ok_or_else(authentication_required)?;
+        let db = Database::from_ref(state);
+        let found = if token.starts_with(API_TOKEN_PREFIX) {
+            api_token_user(&db, &token).await
+        } else {
+            session_user(&db, &token).await.map(|user| (user, Credential::Session))
+        };
+        let (user, credential) = match found {
+            Ok(found) => found,
+            Err(AppError::Unauthorized(message)) => {
+                parts.extensions.insert(Rejected);
+                return Err(AppError::Unauthorized(message));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let user = CurrentUser(user);
+        parts.extensions.insert(user.clone());
+        parts.extensions.insert(credential);
+        Ok(user)
+    }
+}
+
+async fn session_user(db: &Database, token: &str) -> Result<User> {
+    sqlx::query_as!(
+        User,
+        r#"
+        SELECT users.id, users.email, users.display_name,
+               users.role as "role: UserRole", users.created_at
+        FROM sessions
+        JOIN users ON users.id = sessions.user_id
+        WHERE sessions.token_hash = $1 AND sessions.expires_at > NOW()
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(db.pool())
+    .await?
+    .ok_or_else(authentication_required)
+}
+
+async fn api_token_user(db: &Database, token: &str) -> Result<(User, Credential)> {
+    let row = sqlx::query!(
+        r#"
+        SELECT api_tokens.id as token_id, api_tokens.scopes,
+               users.id, users.email, users.display_name,
+               users.role as "role: UserRole", users.created_at
+        FROM api_tokens
+        JOIN users ON users.id = api_tokens.user_id
+        WHERE api_tokens.token_hash = $1
+          AND (api_tokens.expires_at IS NULL OR api_tokens.expires_at > NOW())
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(db.pool())
+    .await?
+    .ok_or_else(authentication_required)?;
+
+    // Recorded at most once a minute so busy scripts do not write on every call
+    sqlx::query!(
+        r#"
+        UPDATE api_tokens SET last_used_at = NOW()
+        WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+        "#,
+        row.token_id
+    )
+    .execute(db.pool())
+    .await?;
+
+    let user = User {
+        id: row.id,
+        email: row.email,
+        display_name: row.display_name,
+        role: row.role,
+        created_at: row.created_at,
+    };
+    let credential = Credential::ApiToken {
+        id: row.token_id,
+        scopes: parse_scopes(&row.scopes),
+    };
+    Ok((user, credential))
+}
+
+/// Scopes stored as permission names; names this build does not know are dropped
+pub fn parse_scopes(names: &[String]) -> Vec<Permission> {
+    names.iter().filter_map(|name| name.parse().ok()).collect()
+}
+
+/// Layer that answers `403 Forbidden` unless the current user has `permission`
+///
+/// Both the user's role and, for API tokens, the token's scopes must allow it.
+/// It must sit inside the [`CurrentUser`] route layer, which authenticates the
+/// request and leaves the user and credential in the request extensions.
+#[derive(Debug, Clone, Copy)]
+pub struct RequirePermission(pub Permission);
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    permission: Permission,
+}
+
+impl<S> Service<Request> for RequirePermissionService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let user = request.extensions().get::<CurrentUser>();
+        let credential = request.extensions().get::<Credential>();
+        let rejection = match (user, credential) {
+            (Some(CurrentUser(user)), Some(_)) if !user.can(self.permission) => {
+                Some(AppError::Forbidden(format!(
+                    "This action requires the {} role",
+                    self.permission.min_role()
+                )))
+            }
+            (Some(_), Some(credential)) if !credential.allows(self.permission) => {
+                Some(AppError::Forbidden(format!(
+                    "This token does not have the {} scope",
+                    self.permission
+                )))
+            }
+            (Some(_), Some(_)) => None,
+            _ => Some(authentication_required()),
+        };
+
+        match rejection {
+            None => Box::pin(self.inner.call(request)),
+            Some(error) => Box::pin(async move { Ok(error.into_response()) }),
+        }
+    }
+}
+
+fn authentication_required() -> AppError {
+    AppError::Unauthorized("Authentication required".to_string())
+}
+
+/// Session token from the `Authorization` header, falling back to the cookie
+pub fn session_token(headers: &HeaderMap) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+
+    bearer.or_else(|| {
+        CookieJar::from_headers(headers)
+            .get(SESSION_COOKIE)
+            .map(|cookie| cookie.value().to_string())
+    })
+}
+
+/// New random session token, URL-safe so it can go in a cookie or header as is
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// What the `sessions` table stores in place of the token itself
+pub fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+/// Argon2id hash of `password` in PHC string format
+///
+/// Hashing is deliberately slow, so it runs on the blocking thread pool.
+pub async fn hash_password(password: String) -> Result<String> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Password hashing task failed: {}", e)))?
+    .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))
+}
+
+/// Checks `password` against a stored hash; a missing hash never matches
+///
+/// Unknown accounts are still checked against a throwaway hash so a login
+/// takes as long whether or not the email exists.
+pub async fn verify_password(password: String, stored_hash: Option<String>) -> Result<bool> {
+    tokio::task::spawn_blocking(move || {
+        let known = stored_hash.is_some();
+        let stored_hash = stored_hash.unwrap_or_else(|| dummy_hash().to_string());
+        let matches = PasswordHash::new(&stored_hash)
+            .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+            .unwrap_or(false);
+        known && matches
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Password check task failed: {}", e)))
+}
+
+fn dummy_hash() -> &'static str {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    DUMMY_HASH.get_or_init(|| {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(b"not a real password", &salt)
+            .map(|hash| hash.to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// Creates the first account from `ADMIN_EMAIL` / `ADMIN_PASSWORD` while no users exist
+pub async fn bootstrap_admin(
+    db: &Database,
+    email: Option<&str>,
+    password: Option<&str>,
+) -> Result<()> {
+    let has_users = sqlx::query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM users) as "exists!""#)
+        .fetch_one(db.pool())
+        .await?;
+    if has_users {
+        return Ok(());
+    }
+
+    let (Some(email), Some(password)) = (email, password) else {
+        tracing::warn!("No users exist; set ADMIN_EMAIL and ADMIN_PASSWORD to create the first account");
+        return Ok(());
+    };
+
+    let password_hash = hash_password(password.to_string()).await?;
+    sqlx::query!(
+        "INSERT INTO users (email, display_name, password_hash, role) VALUES ($1, $2, $3, $4)",
+        email.trim(),
+        "Administrator",
+        password_hash,
+        UserRole::Admin as UserRole
+    )
+    .execute(db.pool())
+    .await?;
+
+    tracing::info!("Created initial account {}", email.trim());
+    Ok(())
+}
@@ -1,4 +1,13 @@
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use log::LevelFilter;
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgConnectOptions, PgPoolOptions},
+    ConnectOptions, PgPool,
+};
+use std::{str::FromStr, time::Duration};
+
+/// Migrations bundled into the binary
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 #[derive(Clone)]
 pub struct Database {
@@ -6,20 +15,69 @@ pub struct Database {
 }
 
 impl Database {
-    pub async fn new(database_url: &str) -> Result<Self, sqlx::Error> {
+    pub async fn new(
+        database_url: &str,
+        max_connections: u32,
+        acquire_timeout: Duration,
+        slow_query_threshold: Duration,
+    ) -> Result<Self, sqlx::Error> {
+        // Every statement is logged at debug level under the `sqlx::query` target,
+        // slow ones as warnings
+        let options = PgConnectOptions::from_str(database_url)?
+            .log_statements(LevelFilter::Debug)
+            .log_slow_statements(LevelFilter::Warn, slow_query_threshold);
+
         let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
+            .max_connections(max_connections)
+            .acquire_timeout(acquire_timeout)
+            .connect_with(options)
             .await?;
 
         Ok(Database { pool })
     }
 
+    #[cfg(test)]
+    pub fn from_pool(pool: PgPool) -> Self {
+        Database { pool }
+    }
+
+    /// A pool that only connects once a query runs
+    #[cfg(test)]
+    pub fn lazy(database_url: &str, acquire_timeout: Duration) -> Result<Self, sqlx::Error> {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(acquire_timeout)
+            .connect_lazy(database_url)?;
+        Ok(Database { pool })
+    }
+
     pub async fn run_migrations(&self) -> Result<(), sqlx::Error> {
-        sqlx::migrate!("./migrations").run(&self.pool).await?;
+        MIGRATOR.run(&self.pool).await?;
         Ok(())
     }
 
+    /// Number of bundled migrations
+    pub fn migration_count(&self) -> usize {
+        MIGRATOR
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .count()
+    }
+
+    /// Versions of bundled migrations the database has not applied successfully
+    pub async fn pending_migrations(&self) -> Result<Vec<i64>, sqlx::Error> {
+        let applied: Vec<i64> =
+            sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(MIGRATOR
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .map(|migration| migration.version)
+            .filter(|version| !applied.contains(version))
+            .collect())
+    }
+
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
@@ -1,44 +1,183 @@
 use axum::{
-    http::StatusCode,
+    body::to_bytes,
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use platform_core::{ApiError, ErrorCode, FieldError, TransitionError, PROBLEM_JSON};
+use std::time::Duration;
+use tower_http::request_id::RequestId;
 
 #[derive(Debug)]
 pub enum AppError {
     Database(sqlx::Error),
+    /// Unexpected server-side failure; the message is logged, not returned
+    Internal(String),
+    /// No valid session, or wrong credentials
+    Unauthorized(String),
+    /// Signed in, but the user's role does not allow the action
+    Forbidden(String),
     NotFound,
     BadRequest(String),
+    Conflict(String),
+    /// A unique value (name, email, key) is already taken
+    AlreadyExists(String),
+    /// The `If-Match` version no longer matches the stored row
+    PreconditionFailed(String),
+    UnprocessableEntity(String),
+    /// One or more request fields are invalid
+    Validation(Vec<FieldError>),
+    /// The client's rate limit is used up; it may retry after this long
+    RateLimited(Duration),
 }
 
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
-        AppError::Database(err)
+        match &err {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                AppError::AlreadyExists(unique_violation_message(db_err.constraint()).to_string())
+            }
+            _ => AppError::Database(err),
+        }
     }
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
+/// User-facing message for a unique constraint, by constraint name
+fn unique_violation_message(constraint: Option<&str>) -> &'static str {
+    match constraint {
+        Some("idx_themes_name_live") => "A theme with this name already exists",
+        Some("users_email_key") => "A user with this email already exists",
+        Some("component_settings_key_key") => "A setting with this key already exists",
+        Some("theme_settings_theme_id_key_key") => "This theme already has a setting with this key",
+        _ => "A record with the same unique value already exists",
+    }
+}
+
+impl From<TransitionError> for AppError {
+    fn from(err: TransitionError) -> Self {
+        match err {
+            TransitionError::NotAllowed { .. } => AppError::Conflict(err.to_string()),
+            TransitionError::ReasonRequired => AppError::UnprocessableEntity(err.to_string()),
+        }
+    }
+}
+
+impl AppError {
+    /// The error as sent to clients; internal details are logged here and left out
+    fn to_api_error(&self) -> ApiError {
+        match self {
             AppError::Database(err) => {
                 tracing::error!("Database error: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                )
+                ApiError::new(ErrorCode::Internal, "Internal server error")
+            }
+            AppError::Internal(msg) => {
+                tracing::error!("Internal error: {}", msg);
+                ApiError::new(ErrorCode::Internal, "Internal server error")
             }
-            AppError::NotFound => (StatusCode::NOT_FOUND, "Resource not found".to_string()),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-        };
+            AppError::Unauthorized(msg) => ApiError::new(ErrorCode::Unauthenticated, msg),
+            AppError::Forbidden(msg) => ApiError::new(ErrorCode::Forbidden, msg),
+            AppError::NotFound => ApiError::new(ErrorCode::NotFound, "Resource not found"),
+            AppError::BadRequest(msg) => ApiError::new(ErrorCode::BadRequest, msg),
+            AppError::Conflict(msg) => ApiError::new(ErrorCode::Conflict, msg),
+            AppError::AlreadyExists(msg) => ApiError::new(ErrorCode::AlreadyExists, msg),
+            AppError::PreconditionFailed(msg) => ApiError::new(ErrorCode::PreconditionFailed, msg),
+            AppError::UnprocessableEntity(msg) => ApiError::new(ErrorCode::Unprocessable, msg),
+            AppError::Validation(fields) => ApiError {
+                fields: fields.clone(),
+                ..ApiError::new(ErrorCode::ValidationFailed, "Some fields are invalid")
+            },
+            AppError::RateLimited(wait) => ApiError::new(
+                ErrorCode::RateLimited,
+                format!("Too many requests; try again in {} seconds", retry_after_secs(*wait)),
+            ),
+        }
+    }
+}
 
-        let body = Json(json!({
-            "error": error_message,
-        }));
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let mut response = error_response(self.to_api_error(), false);
+        if let AppError::RateLimited(wait) = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs(wait)));
+        }
+        response
+    }
+}
+
+/// Whole seconds for `Retry-After`, rounded up so clients never retry too early
+fn retry_after_secs(wait: Duration) -> u64 {
+    wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+}
+
+fn error_response(error: ApiError, problem: bool) -> Response {
+    let status =
+        StatusCode::from_u16(error.code.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
 
-        (status, body).into_response()
+    let mut response = if problem {
+        let mut response = (status, Json(error.to_problem())).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    } else {
+        (status, Json(error.clone())).into_response()
+    };
+    // Lets `finish_errors` tell our own errors from axum's plain-text rejections
+    response.extensions_mut().insert(error);
+    response
+}
+
+/// Middleware giving every error response the [`ApiError`] shape
+///
+/// Adds the request id, renders a problem document when the client's `Accept`
+/// header asks for `application/problem+json`, and wraps errors that never
+/// went through [`AppError`] (unknown routes, extractor rejections) the same
+/// way. Must run inside the layer that sets the request id.
+pub async fn finish_errors(request: Request, next: Next) -> Response {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .map(str::to_string);
+    let problem = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains(PROBLEM_JSON));
+
+    let response = next.run(request).await;
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
     }
+
+    let (mut parts, body) = response.into_parts();
+    let mut error = match parts.extensions.remove::<ApiError>() {
+        Some(error) => error,
+        None => {
+            let text = to_bytes(body, 64 * 1024).await.unwrap_or_default();
+            let text = String::from_utf8_lossy(&text).trim().to_string();
+            let code = ErrorCode::from_status(status.as_u16());
+            let message = if text.is_empty() || code == ErrorCode::Internal {
+                code.title().to_string()
+            } else {
+                text
+            };
+            ApiError::new(code, message)
+        }
+    };
+    error.request_id = request_id;
+
+    let mut rendered = error_response(error, problem);
+    // Keep the original status and headers such as `Allow` or `WWW-Authenticate`
+    *rendered.status_mut() = status;
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.extend(rendered.headers_mut().drain());
+    Response::from_parts(parts, rendered.into_body())
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
-
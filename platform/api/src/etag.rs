@@ -0,0 +1,89 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+    response::{IntoResponse, Response},
+    Json,
+};
+use platform_core::{etag, parse_etag, ComponentSetting, Theme, ThemeWithSettings, Todo};
+use serde::Serialize;
+
+use crate::error::{AppError, Result};
+
+/// A record that carries a row version for optimistic concurrency
+pub trait Versioned {
+    fn version(&self) -> i32;
+}
+
+impl Versioned for Todo {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
+impl Versioned for Theme {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
+/// Updating a setting bumps its theme's version, so this covers the settings too
+impl Versioned for ThemeWithSettings {
+    fn version(&self) -> i32 {
+        self.theme.version
+    }
+}
+
+impl Versioned for ComponentSetting {
+    fn version(&self) -> i32 {
+        self.version
+    }
+}
+
+/// JSON response with an `ETag` header holding the record's version
+pub struct Tagged<T>(pub T);
+
+impl<T: Serialize + Versioned> IntoResponse for Tagged<T> {
+    fn into_response(self) -> Response {
+        ([(header::ETAG, etag(self.0.version()))], Json(self.0)).into_response()
+    }
+}
+
+/// Versions listed in the request's `If-Match` header
+///
+/// Holds `None` when the header is missing or `*`, in which case any version
+/// may be overwritten.
+pub struct IfMatch(Option<Vec<i32>>);
+
+impl IfMatch {
+    /// Fails with `412 Precondition Failed` unless `current` is an expected version
+    pub fn check(&self, current: i32) -> Result<()> {
+        match &self.0 {
+            Some(versions) if !versions.contains(&current) => Err(AppError::PreconditionFailed(
+                "The resource was modified since it was read; reload it and try again".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(IfMatch(None));
+        };
+        let value = value
+            .to_str()
+            .map_err(|_| AppError::BadRequest("Invalid If-Match header".to_string()))?;
+
+        if value.trim() == "*" {
+            return Ok(IfMatch(None));
+        }
+
+        // Tags that are not one of our versions can never match, so they are dropped
+        Ok(IfMatch(Some(value.split(',').filter_map(parse_etag).collect())))
+    }
+}
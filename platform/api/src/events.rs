@@ -0,0 +1,151 @@
+//! Live change feed at `/events`
+//!
+//! Triggers on `todos`, `themes` and `theme_settings` publish a
+//! [`ChangeEvent`] on the [`CHANNEL`] Postgres channel for every write. One
+//! listener task per server forwards them to an [`EventBus`], and each open
+//! feed streams them to its client as server-sent events.
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use platform_core::ChangeEvent;
+use sqlx::postgres::PgListener;
+use std::{convert::Infallible, sync::Arc, time::Duration};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    watch,
+};
+
+use crate::db::Database;
+
+/// Postgres channel the `notify_change` trigger publishes on
+pub const CHANNEL: &str = "atom_changes";
+
+/// Events a slow feed may fall behind by before it is closed
+const BUFFERED_EVENTS: usize = 256;
+
+/// Wait before reconnecting a listener whose connection failed
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Fans changes out to every open feed; cheap to clone
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ChangeEvent>,
+    /// Set on shutdown so feeds end and the listener returns its connection
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(BUFFERED_EVENTS);
+        let (closed, _) = watch::channel(false);
+        Self {
+            sender,
+            closed: Arc::new(closed),
+        }
+    }
+
+    /// Ends every feed and stops the listener; feeds never finish on their own
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    async fn wait_closed(mut closed: watch::Receiver<bool>) {
+        // An error means the bus is gone, which is as closed as it gets
+        let _ = closed.wait_for(|closed| *closed).await;
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Starts the task forwarding notifications from Postgres to `bus`
+///
+/// The listener holds one pool connection until the bus is closed. Changes
+/// made while it reconnects are not delivered.
+pub fn spawn_listener(db: Database, bus: EventBus) {
+    tokio::spawn(async move {
+        let closed = bus.closed.subscribe();
+        loop {
+            tokio::select! {
+                _ = EventBus::wait_closed(closed.clone()) => return,
+                result = listen(&db, &bus) => {
+                    if let Err(e) = result {
+                        tracing::error!("Change feed listener failed, retrying in {:?}: {}", RETRY_DELAY, e);
+                    }
+                }
+            }
+            tokio::select! {
+                _ = EventBus::wait_closed(closed.clone()) => return,
+                _ = tokio::time::sleep(RETRY_DELAY) => {}
+            }
+        }
+    });
+}
+
+async fn listen(db: &Database, bus: &EventBus) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(db.pool()).await?;
+    listener.listen(CHANNEL).await?;
+    tracing::info!("Listening for changes on {}", CHANNEL);
+
+    loop {
+        // Reconnects by itself if the connection drops
+        let notification = listener.recv().await?;
+        match serde_json::from_str::<ChangeEvent>(notification.payload()) {
+            // Fails only while no feed is open
+            Ok(event) => {
+                let _ = bus.sender.send(event);
+            }
+            Err(e) => tracing::warn!(
+                "Ignoring malformed change notification {:?}: {}",
+                notification.payload(),
+                e
+            ),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/events",
+    tag = "events",
+    responses(
+        (
+            status = 200,
+            description = "Server-sent events, one JSON `ChangeEvent` per message, until the server shuts down. A feed that falls too far behind is closed; reconnect and fetch again.",
+            content_type = "text/event-stream",
+            body = ChangeEvent
+        ),
+    )
+)]
+pub async fn events(
+    State(bus): State<EventBus>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = bus.sender.subscribe();
+    let closed = bus.closed.subscribe();
+
+    let stream = stream::unfold((receiver, closed), |(mut receiver, closed)| async move {
+        let received = tokio::select! {
+            _ = EventBus::wait_closed(closed.clone()) => return None,
+            received = receiver.recv() => received,
+        };
+        match received {
+            Ok(event) => {
+                let data = serde_json::to_string(&event).expect("change events serialize");
+                Some((Ok(Event::default().data(data)), (receiver, closed)))
+            }
+            Err(RecvError::Lagged(missed)) => {
+                tracing::warn!("Closing a change feed that missed {} events", missed);
+                None
+            }
+            Err(RecvError::Closed) => None,
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,121 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use chrono::Utc;
+use platform_core::{ApiError, LoginRequest, LoginResponse, User, UserRole};
+
+use crate::{
+    auth::{generate_token, hash_token, session_token, verify_password, CurrentUser, SESSION_COOKIE},
+    error::{AppError, Result},
+    state::AppState,
+};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    security(()),
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Signed in; the token is also set as the `atom_session` cookie", body = LoginResponse),
+        (status = 401, description = "Wrong email or password", body = ApiError),
+        (status = 429, description = "Too many sign-in attempts; see `Retry-After`", body = ApiError),
+    )
+)]
+pub async fn login(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(payload): Json<LoginRequest>,
+) -> Result<(CookieJar, Json<LoginResponse>)> {
+    let account = sqlx::query!(
+        r#"
+        SELECT id, email, display_name, role as "role: UserRole", created_at, password_hash
+        FROM users
+        WHERE lower(email) = lower($1)
+        "#,
+        payload.email.trim()
+    )
+    .fetch_optional(state.db.pool())
+    .await?;
+
+    let stored_hash = account.as_ref().map(|account| account.password_hash.clone());
+    let verified = verify_password(payload.password, stored_hash).await?;
+    let account = match account {
+        Some(account) if verified => account,
+        _ => return Err(AppError::Unauthorized("Invalid email or password".to_string())),
+    };
+
+    let token = generate_token();
+    let expires_at = Utc::now() + state.session_ttl;
+    sqlx::query!(
+        "INSERT INTO sessions (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+        account.id,
+        hash_token(&token),
+        expires_at
+    )
+    .execute(state.db.pool())
+    .await?;
+
+    // Outlives the browser session like the token clients keep, so the change
+    // feed, which signs in with the cookie, keeps working until expires_at
+    let mut cookie = session_cookie(&state, token.clone());
+    cookie.set_max_age(time::Duration::seconds(state.session_ttl.num_seconds()));
+
+    let response = LoginResponse {
+        token,
+        expires_at,
+        user: User {
+            id: account.id,
+            email: account.email,
+            display_name: account.display_name,
+            role: account.role,
+            created_at: account.created_at,
+        },
+    };
+
+    Ok((jar.add(cookie), Json(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    tag = "auth",
+    responses((status = 204, description = "Session ended"))
+)]
+pub async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+) -> Result<(CookieJar, StatusCode)> {
+    if let Some(token) = session_token(&headers) {
+        sqlx::query!("DELETE FROM sessions WHERE token_hash = $1", hash_token(&token))
+            .execute(state.db.pool())
+            .await?;
+    }
+
+    // Browsers only drop the cookie when the removal has the same attributes
+    Ok((jar.remove(session_cookie(&state, String::new())), StatusCode::NO_CONTENT))
+}
+
+/// The session cookie, with the attributes used both to set and to remove it
+fn session_cookie(state: &AppState, token: String) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE, token))
+        .path("/")
+        .http_only(true)
+        .secure(state.secure_cookies)
+        .same_site(SameSite::Lax)
+        .build()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/me",
+    tag = "auth",
+    responses((status = 200, description = "The signed-in user", body = User))
+)]
+pub async fn me(CurrentUser(user): CurrentUser) -> Json<User> {
+    Json(user)
+}
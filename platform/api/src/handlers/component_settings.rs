@@ -2,11 +2,25 @@ use axum::{
     extract::{Path, State},
     Json,
 };
-use platform_core::{ComponentSetting, ComponentSettingUpdate};
+use platform_core::{
+    setting_value_error, ApiError, ComponentSetting, ComponentSettingUpdate, Validator, MAX_SETTING_VALUE_CHARS,
+};
 use uuid::Uuid;
 
-use crate::{db::Database, error::Result};
+use crate::{
+    db::Database,
+    error::{AppError, Result},
+    etag::{IfMatch, Tagged},
+    update::UpdateBuilder,
+    validation::ValidJson,
+};
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/settings",
+    tag = "settings",
+    responses((status = 200, description = "Every setting, by category and key", body = Vec<ComponentSetting>))
+)]
 pub async fn list_settings(State(db): State<Database>) -> Result<Json<Vec<ComponentSetting>>> {
     let settings = sqlx::query_as!(
         ComponentSetting,
@@ -18,7 +32,8 @@ pub async fn list_settings(State(db): State<Database>) -> Result<Json<Vec<Compon
             category,
             description,
             created_at,
-            updated_at
+            updated_at,
+            version
         FROM component_settings
         ORDER BY category, key
         "#
@@ -29,10 +44,20 @@ pub async fn list_settings(State(db): State<Database>) -> Result<Json<Vec<Compon
     Ok(Json(settings))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/settings/{id}",
+    tag = "settings",
+    params(("id" = Uuid, Path, description = "Setting id")),
+    responses(
+        (status = 200, description = "The setting", body = ComponentSetting, headers(("ETag" = String, description = "Row version"))),
+        (status = 404, description = "No such setting", body = ApiError),
+    )
+)]
 pub async fn get_setting(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
-) -> Result<Json<ComponentSetting>> {
+) -> Result<Tagged<ComponentSetting>> {
     let setting = sqlx::query_as!(
         ComponentSetting,
         r#"
@@ -43,7 +68,8 @@ pub async fn get_setting(
             category,
             description,
             created_at,
-            updated_at
+            updated_at,
+            version
         FROM component_settings
         WHERE id = $1
         "#,
@@ -51,56 +77,76 @@ pub async fn get_setting(
     )
     .fetch_optional(db.pool())
     .await?
-    .ok_or(crate::error::AppError::NotFound)?;
+    .ok_or(AppError::NotFound)?;
 
-    Ok(Json(setting))
+    Ok(Tagged(setting))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/settings/{id}",
+    tag = "settings",
+    params(
+        ("id" = Uuid, Path, description = "Setting id"),
+        ("If-Match" = Option<String>, Header, description = "Expected row version"),
+    ),
+    request_body = ComponentSettingUpdate,
+    responses(
+        (status = 200, description = "Updated setting", body = ComponentSetting, headers(("ETag" = String, description = "Row version"))),
+        (status = 404, description = "No such setting", body = ApiError),
+        (status = 412, description = "The setting changed since `If-Match`", body = ApiError),
+        (status = 422, description = "Value does not fit the setting's category", body = ApiError),
+    )
+)]
 pub async fn update_setting(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<ComponentSettingUpdate>,
-) -> Result<Json<ComponentSetting>> {
-    let _existing = sqlx::query!("SELECT id FROM component_settings WHERE id = $1", id)
-        .fetch_optional(db.pool())
-        .await?
-        .ok_or(crate::error::AppError::NotFound)?;
-
-    // Simple update focusing on value changes
-    if let Some(value) = payload.value {
-        let setting = sqlx::query_as!(
-            ComponentSetting,
-            r#"
-            UPDATE component_settings 
-            SET value = $1, updated_at = NOW()
-            WHERE id = $2
-            RETURNING id, key, value, category, description, created_at, updated_at
-            "#,
-            value,
-            id
-        )
-        .fetch_one(db.pool())
-        .await?;
+    if_match: IfMatch,
+    ValidJson(payload): ValidJson<ComponentSettingUpdate>,
+) -> Result<Tagged<ComponentSetting>> {
+    let mut tx = db.pool().begin().await?;
 
-        Ok(Json(setting))
-    } else if let Some(description) = payload.description {
-        let setting = sqlx::query_as!(
-            ComponentSetting,
-            r#"
-            UPDATE component_settings 
-            SET description = $1, updated_at = NOW()
-            WHERE id = $2
-            RETURNING id, key, value, category, description, created_at, updated_at
-            "#,
-            description,
-            id
-        )
-        .fetch_one(db.pool())
-        .await?;
+    let current = sqlx::query!(
+        "SELECT version, category FROM component_settings WHERE id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    if_match.check(current.version)?;
+    if let Some(value) = &payload.value {
+        check_setting_value(&current.category, value)?;
+    }
 
-        Ok(Json(setting))
-    } else {
+    let mut update = UpdateBuilder::new("component_settings");
+    update
+        .set("value", payload.value)
+        .patch("description", payload.description);
+    if !update.has_changes() {
         // No changes, just return existing
-        get_setting(State(db), Path(id)).await
+        return get_setting(State(db), Path(id)).await;
     }
+
+    let mut query = update.finish();
+    query
+        .push("id = ")
+        .push_bind(id)
+        .push(" RETURNING id, key, value, category, description, created_at, updated_at, version");
+    let setting = query
+        .build_query_as::<ComponentSetting>()
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Tagged(setting))
+}
+
+/// `422` unless `value` has the format the setting's category expects
+pub fn check_setting_value(category: &str, value: &str) -> Result<()> {
+    Validator::new()
+        .max_chars("value", value, MAX_SETTING_VALUE_CHARS)
+        .format("value", setting_value_error(category, value))
+        .finish()
+        .map_err(AppError::Validation)
 }
@@ -2,3 +2,6 @@ pub mod todos;
 pub mod component_settings;
 pub mod themes;
 
+pub mod auth;
+pub mod users;
+pub mod tokens;
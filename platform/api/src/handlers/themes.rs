@@ -1,21 +1,41 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
-use platform_core::{Theme, ThemeWithSettings, ThemeCreate, ThemeUpdate, ComponentSetting};
+use platform_core::{ApiError, Theme, ThemeWithSettings, ThemeCreate, ThemeUpdate, ComponentSetting, DeletedQuery};
 use uuid::Uuid;
 
-use crate::{db::Database, error::Result};
+use crate::{
+    db::Database,
+    handlers::component_settings::check_setting_value,
+    error::{AppError, Result},
+    etag::{IfMatch, Tagged},
+    metrics::Metrics,
+    update::UpdateBuilder,
+    validation::ValidJson,
+};
 
-pub async fn list_themes(State(db): State<Database>) -> Result<Json<Vec<Theme>>> {
+#[utoipa::path(
+    get,
+    path = "/api/v1/themes",
+    tag = "themes",
+    params(DeletedQuery),
+    responses((status = 200, description = "Themes", body = Vec<Theme>))
+)]
+pub async fn list_themes(
+    State(db): State<Database>,
+    Query(params): Query<DeletedQuery>,
+) -> Result<Json<Vec<Theme>>> {
     let themes = sqlx::query_as!(
         Theme,
         r#"
-        SELECT id, name, is_active, created_at, updated_at
+        SELECT id, name, is_active, created_at, updated_at, deleted_at, version
         FROM themes
+        WHERE $1 OR deleted_at IS NULL
         ORDER BY name
-        "#
+        "#,
+        params.include_deleted
     )
     .fetch_all(db.pool())
     .await?;
@@ -23,27 +43,39 @@ pub async fn list_themes(State(db): State<Database>) -> Result<Json<Vec<Theme>>>
     Ok(Json(themes))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/themes/{id}",
+    tag = "themes",
+    params(("id" = Uuid, Path, description = "Theme id"), DeletedQuery),
+    responses(
+        (status = 200, description = "The theme and its settings", body = ThemeWithSettings, headers(("ETag" = String, description = "Theme row version, bumped whenever one of its settings changes"))),
+        (status = 404, description = "No such theme", body = ApiError),
+    )
+)]
 pub async fn get_theme_with_settings(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
-) -> Result<Json<ThemeWithSettings>> {
+    Query(params): Query<DeletedQuery>,
+) -> Result<Tagged<ThemeWithSettings>> {
     let theme = sqlx::query_as!(
         Theme,
         r#"
-        SELECT id, name, is_active, created_at, updated_at
+        SELECT id, name, is_active, created_at, updated_at, deleted_at, version
         FROM themes
-        WHERE id = $1
+        WHERE id = $1 AND ($2 OR deleted_at IS NULL)
         "#,
-        id
+        id,
+        params.include_deleted
     )
     .fetch_optional(db.pool())
     .await?
-    .ok_or(crate::error::AppError::NotFound)?;
+    .ok_or(AppError::NotFound)?;
 
     let settings = sqlx::query_as!(
         ComponentSetting,
         r#"
-        SELECT id, key, value, category, description, created_at, updated_at
+        SELECT id, key, value, category, description, created_at, updated_at, version
         FROM theme_settings
         WHERE theme_id = $1
         ORDER BY category, key
@@ -53,19 +85,32 @@ pub async fn get_theme_with_settings(
     .fetch_all(db.pool())
     .await?;
 
-    Ok(Json(ThemeWithSettings { theme, settings }))
+    Ok(Tagged(ThemeWithSettings { theme, settings }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/themes",
+    tag = "themes",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when a request is retried with the same key")),
+    request_body = ThemeCreate,
+    responses(
+        (status = 201, description = "Theme created", body = Theme),
+        (status = 409, description = "The name is taken, or a request with the same Idempotency-Key is running", body = ApiError),
+        (status = 422, description = "Invalid fields, or the Idempotency-Key was used for a different request", body = ApiError),
+        (status = 429, description = "Too many themes created; see `Retry-After`", body = ApiError),
+    )
+)]
 pub async fn create_theme(
     State(db): State<Database>,
-    Json(payload): Json<ThemeCreate>,
+    ValidJson(payload): ValidJson<ThemeCreate>,
 ) -> Result<(StatusCode, Json<Theme>)> {
     let theme = sqlx::query_as!(
         Theme,
         r#"
         INSERT INTO themes (name, is_active)
         VALUES ($1, false)
-        RETURNING id, name, is_active, created_at, updated_at
+        RETURNING id, name, is_active, created_at, updated_at, deleted_at, version
         "#,
         payload.name
     )
@@ -93,114 +138,214 @@ pub async fn create_theme(
     Ok((StatusCode::CREATED, Json(theme)))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/themes/{id}",
+    tag = "themes",
+    params(
+        ("id" = Uuid, Path, description = "Theme id"),
+        ("If-Match" = Option<String>, Header, description = "Expected row version"),
+    ),
+    request_body = ThemeUpdate,
+    responses(
+        (status = 200, description = "Updated theme", body = Theme, headers(("ETag" = String, description = "Row version"))),
+        (status = 404, description = "No such theme", body = ApiError),
+        (status = 409, description = "The name is taken", body = ApiError),
+        (status = 412, description = "The theme changed since `If-Match`", body = ApiError),
+    )
+)]
 pub async fn update_theme(
     State(db): State<Database>,
+    State(metrics): State<Metrics>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<ThemeUpdate>,
-) -> Result<Json<Theme>> {
-    let _existing = sqlx::query!("SELECT id FROM themes WHERE id = $1", id)
-        .fetch_optional(db.pool())
-        .await?
-        .ok_or(crate::error::AppError::NotFound)?;
+    if_match: IfMatch,
+    ValidJson(payload): ValidJson<ThemeUpdate>,
+) -> Result<Tagged<Theme>> {
+    let mut update = UpdateBuilder::new("themes");
+    update
+        .set("name", payload.name)
+        .set("is_active", payload.is_active);
+    if !update.has_changes() {
+        return Err(AppError::BadRequest("No fields to update".to_string()));
+    }
+
+    let mut tx = db.pool().begin().await?;
 
-    // If setting as active, deactivate all others first
+    let current = sqlx::query_scalar!(
+        "SELECT version FROM themes WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    if_match.check(current)?;
+
+    // If setting as active, deactivate the previously active theme in the same transaction
     if payload.is_active == Some(true) {
-        sqlx::query!("UPDATE themes SET is_active = false")
-            .execute(db.pool())
+        sqlx::query!("UPDATE themes SET is_active = false WHERE id <> $1 AND is_active", id)
+            .execute(&mut *tx)
             .await?;
     }
 
-    let theme = if let Some(name) = payload.name {
-        if let Some(is_active) = payload.is_active {
-            sqlx::query_as!(
-                Theme,
-                r#"
-                UPDATE themes
-                SET name = $1, is_active = $2, updated_at = NOW()
-                WHERE id = $3
-                RETURNING id, name, is_active, created_at, updated_at
-                "#,
-                name,
-                is_active,
-                id
-            )
-            .fetch_one(db.pool())
-            .await?
-        } else {
-            sqlx::query_as!(
-                Theme,
-                r#"
-                UPDATE themes
-                SET name = $1, updated_at = NOW()
-                WHERE id = $2
-                RETURNING id, name, is_active, created_at, updated_at
-                "#,
-                name,
-                id
-            )
-            .fetch_one(db.pool())
-            .await?
-        }
-    } else if let Some(is_active) = payload.is_active {
-        sqlx::query_as!(
-            Theme,
-            r#"
-            UPDATE themes
-            SET is_active = $1, updated_at = NOW()
-            WHERE id = $2
-            RETURNING id, name, is_active, created_at, updated_at
-            "#,
-            is_active,
-            id
-        )
-        .fetch_one(db.pool())
-        .await?
-    } else {
-        return Err(crate::error::AppError::BadRequest(
-            "No fields to update".to_string(),
-        ));
-    };
+    let mut query = update.finish();
+    query
+        .push("id = ")
+        .push_bind(id)
+        .push(" RETURNING id, name, is_active, created_at, updated_at, deleted_at, version");
+    let theme = query.build_query_as::<Theme>().fetch_one(&mut *tx).await?;
 
-    Ok(Json(theme))
+    tx.commit().await?;
+
+    if payload.is_active == Some(true) {
+        metrics.theme_activated();
+    }
+
+    Ok(Tagged(theme))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/themes/{id}",
+    tag = "themes",
+    params(("id" = Uuid, Path, description = "Theme id")),
+    responses(
+        (status = 204, description = "Moved to the trash"),
+        (status = 400, description = "The theme is active", body = ApiError),
+        (status = 404, description = "No such theme, or it is already in the trash", body = ApiError),
+    )
+)]
 pub async fn delete_theme(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode> {
-    let result = sqlx::query!("DELETE FROM themes WHERE id = $1 AND is_active = false", id)
-        .execute(db.pool())
-        .await?;
+    let mut tx = db.pool().begin().await?;
 
-    if result.rows_affected() == 0 {
-        return Err(crate::error::AppError::BadRequest(
-            "Cannot delete active theme".to_string(),
-        ));
+    let theme = sqlx::query!(
+        "SELECT is_active FROM themes WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    if theme.is_active {
+        return Err(AppError::BadRequest("Cannot delete active theme".to_string()));
     }
 
+    // Soft delete; the purge job removes the row once the retention period has passed
+    sqlx::query!("UPDATE themes SET deleted_at = NOW() WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/themes/{id}/restore",
+    tag = "themes",
+    params(("id" = Uuid, Path, description = "Theme id")),
+    responses(
+        (status = 200, description = "Restored theme", body = Theme),
+        (status = 404, description = "No such theme in the trash", body = ApiError),
+        (status = 409, description = "Another theme has taken the name", body = ApiError),
+    )
+)]
+pub async fn restore_theme(
+    State(db): State<Database>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Theme>> {
+    let theme = sqlx::query_as!(
+        Theme,
+        r#"
+        UPDATE themes
+        SET deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        RETURNING id, name, is_active, created_at, updated_at, deleted_at, version
+        "#,
+        id
+    )
+    .fetch_optional(db.pool())
+    .await
+    .map_err(|err| match err {
+        // Another live theme has taken the name since this one was deleted
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => AppError::AlreadyExists(
+            "Another theme already uses this name; rename that theme before restoring".to_string(),
+        ),
+        other => other.into(),
+    })?
+    .ok_or(AppError::NotFound)?;
+
+    Ok(Json(theme))
+}
+
 // Theme settings endpoints
+#[utoipa::path(
+    put,
+    path = "/api/v1/themes/{theme_id}/settings/{setting_id}",
+    tag = "themes",
+    params(
+        ("theme_id" = Uuid, Path, description = "Theme id"),
+        ("setting_id" = Uuid, Path, description = "Setting id"),
+        ("If-Match" = Option<String>, Header, description = "Expected row version"),
+    ),
+    request_body(content = String, description = "The new value as a JSON string"),
+    responses(
+        (status = 200, description = "Updated setting", body = ComponentSetting, headers(("ETag" = String, description = "Row version"))),
+        (status = 404, description = "No such setting in this theme, or the theme is in the trash", body = ApiError),
+        (status = 412, description = "The setting changed since `If-Match`", body = ApiError),
+        (status = 422, description = "Value does not fit the setting's category", body = ApiError),
+    )
+)]
 pub async fn update_theme_setting(
     State(db): State<Database>,
     Path((theme_id, setting_id)): Path<(Uuid, Uuid)>,
+    if_match: IfMatch,
     Json(value): Json<String>,
-) -> Result<Json<ComponentSetting>> {
+) -> Result<Tagged<ComponentSetting>> {
+    let mut tx = db.pool().begin().await?;
+
+    // Settings of a theme in the trash stay as they were
+    sqlx::query_scalar!(
+        "SELECT id FROM themes WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        theme_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let current = sqlx::query!(
+        "SELECT version, category FROM theme_settings WHERE theme_id = $1 AND id = $2 FOR UPDATE",
+        theme_id,
+        setting_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    if_match.check(current.version)?;
+    check_setting_value(&current.category, &value)?;
+
     let setting = sqlx::query_as!(
         ComponentSetting,
         r#"
         UPDATE theme_settings
         SET value = $1, updated_at = NOW()
-        WHERE theme_id = $2 AND id = $3
-        RETURNING id, key, value, category, description, created_at, updated_at
+        WHERE id = $2
+        RETURNING id, key, value, category, description, created_at, updated_at, version
         "#,
         value,
-        theme_id,
         setting_id
     )
-    .fetch_one(db.pool())
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(Json(setting))
+    // The theme's ETag covers its settings, so a setting change is a theme change
+    sqlx::query!("UPDATE themes SET updated_at = NOW() WHERE id = $1", theme_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Tagged(setting))
 }
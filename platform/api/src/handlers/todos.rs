@@ -1,167 +1,1118 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
-use platform_core::{Todo, TodoCreate, TodoStatus, TodoUpdate};
+use platform_core::{
+    normalize_tags, todo_changes, ApiError, DeletedQuery, Page, SortOrder, Todo, TodoBulkAction, TodoBulkItemResult,
+    TodoBulkOutcome, TodoBulkRequest, TodoBulkResponse, TodoCreate, TodoEvent, TodoEventKind,
+    TodoFieldChange, TodoListQuery, TodoMove, TodoPriority, TodoSearchHit, TodoSearchQuery, TodoSortField,
+    TodoStatus, TodoUpdate, TransitionError, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT,
+};
+use sqlx::{types::Json as SqlJson, Executor, Postgres, QueryBuilder, Transaction};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::{db::Database, error::Result};
+use crate::{
+    auth::CurrentUser,
+    db::Database,
+    error::{AppError, Result},
+    etag::{IfMatch, Tagged},
+    update::UpdateBuilder,
+    validation::ValidJson,
+};
 
-pub async fn list_todos(State(db): State<Database>) -> Result<Json<Vec<Todo>>> {
-    let todos = sqlx::query_as!(
-        Todo,
+#[utoipa::path(
+    get,
+    path = "/api/v1/todos",
+    tag = "todos",
+    params(TodoListQuery),
+    responses(
+        (status = 200, description = "One page of matching todos", body = Page<Todo>),
+        (status = 400, description = "Invalid filter or paging parameters", body = ApiError),
+    )
+)]
+pub async fn list_todos(
+    State(db): State<Database>,
+    CurrentUser(user): CurrentUser,
+    Query(params): Query<TodoListQuery>,
+) -> Result<Json<Page<Todo>>> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_PAGE_LIMIT)
+        .clamp(1, MAX_PAGE_LIMIT);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let mut count_query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM todos");
+    push_list_filters(&mut count_query, &params, user.id);
+    let total: i64 = count_query
+        .build_query_scalar()
+        .fetch_one(db.pool())
+        .await?;
+
+    let mut query = QueryBuilder::<Postgres>::new(
         r#"
-        SELECT 
+        SELECT
             id,
+            parent_id,
             title,
             description,
-            status as "status: TodoStatus",
+            status,
+            cancellation_reason,
+            priority,
+            due_at,
+            ARRAY(
+                SELECT tags.name FROM todo_tags
+                JOIN tags ON tags.id = todo_tags.tag_id
+                WHERE todo_tags.todo_id = todos.id
+                ORDER BY tags.name
+            ) as tags,
+            owner_id,
+            assignee_id,
             created_at,
-            updated_at
+            updated_at,
+            deleted_at,
+            version,
+            progress.total as subtask_total,
+            progress.completed as subtask_completed
         FROM todos
-        ORDER BY created_at DESC
-        "#
+        CROSS JOIN LATERAL todo_subtask_progress(todos.id) progress
+        "#,
+    );
+    push_list_filters(&mut query, &params, user.id);
+    query
+        .push(" ORDER BY ")
+        .push(sort_column(params.sort))
+        .push(match params.order {
+            SortOrder::Asc => " ASC NULLS LAST",
+            SortOrder::Desc => " DESC NULLS LAST",
+        })
+        // Tie-break on id so pages are stable when the sort column has duplicates
+        .push(", id")
+        .push(" LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let items = query.build_query_as::<Todo>().fetch_all(db.pool()).await?;
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Appends the WHERE clause shared by the list and count queries
+///
+/// `user_id` is the caller, for the `mine` filter.
+fn push_list_filters(query: &mut QueryBuilder<'_, Postgres>, params: &TodoListQuery, user_id: Uuid) {
+    query.push(" WHERE TRUE");
+
+    if params.only_deleted {
+        query.push(" AND deleted_at IS NOT NULL");
+    } else if !params.include_deleted {
+        query.push(" AND deleted_at IS NULL");
+    }
+
+    if params.top_level {
+        query.push(" AND parent_id IS NULL");
+    }
+
+    if params.mine {
+        query
+            .push(" AND (assignee_id = ")
+            .push_bind(user_id)
+            .push(" OR (assignee_id IS NULL AND owner_id = ")
+            .push_bind(user_id)
+            .push("))");
+    }
+
+    if !params.status.is_empty() {
+        query
+            .push(" AND status = ANY(")
+            .push_bind(params.status.clone())
+            .push(")");
+    }
+
+    if !params.priority.is_empty() {
+        query
+            .push(" AND priority = ANY(")
+            .push_bind(params.priority.clone())
+            .push(")");
+    }
+
+    let tags = normalize_tags(&params.tag);
+    if !tags.is_empty() {
+        query
+            .push(
+                " AND EXISTS (SELECT 1 FROM todo_tags JOIN tags ON tags.id = todo_tags.tag_id \
+                 WHERE todo_tags.todo_id = todos.id AND tags.name = ANY(",
+            )
+            .push_bind(tags)
+            .push("))");
+    }
+
+    if let Some(due_before) = params.due_before {
+        query.push(" AND due_at < ").push_bind(due_before);
+    }
+
+    if let Some(due_after) = params.due_after {
+        query.push(" AND due_at >= ").push_bind(due_after);
+    }
+
+    if let Some(q) = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        let pattern = format!("%{}%", escape_like(q));
+        query
+            .push(" AND (title ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR description ILIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+}
+
+/// Column backing each sort field; never interpolate user input here
+fn sort_column(field: TodoSortField) -> &'static str {
+    match field {
+        TodoSortField::CreatedAt => "created_at",
+        TodoSortField::UpdatedAt => "updated_at",
+        TodoSortField::Title => "title",
+        TodoSortField::Status => "status",
+        TodoSortField::Priority => "priority",
+        TodoSortField::DueAt => "due_at",
+    }
+}
+
+/// Escapes LIKE wildcards so the search term is matched literally
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/todos/search",
+    tag = "todos",
+    params(TodoSearchQuery),
+    responses((status = 200, description = "Hits, most relevant first", body = Vec<TodoSearchHit>))
+)]
+pub async fn search_todos(
+    State(db): State<Database>,
+    Query(params): Query<TodoSearchQuery>,
+) -> Result<Json<Vec<TodoSearchHit>>> {
+    let q = params.q.trim();
+    if q.is_empty() {
+        return Err(AppError::BadRequest(
+            "Search query must not be empty".to_string(),
+        ));
+    }
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_PAGE_LIMIT)
+        .clamp(1, MAX_PAGE_LIMIT);
+
+    // Highlight markers must match platform_core::HIGHLIGHT_START / HIGHLIGHT_END
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            t.id,
+            t.parent_id,
+            t.title,
+            t.description,
+            t.status as "status: TodoStatus",
+            t.cancellation_reason,
+            t.priority as "priority: TodoPriority",
+            t.due_at,
+            ARRAY(
+                SELECT tags.name FROM todo_tags
+                JOIN tags ON tags.id = todo_tags.tag_id
+                WHERE todo_tags.todo_id = t.id
+                ORDER BY tags.name
+            ) as "tags!",
+            t.owner_id,
+            t.assignee_id,
+            t.created_at,
+            t.updated_at,
+            t.deleted_at,
+            t.version,
+            progress.total as "subtask_total!",
+            progress.completed as "subtask_completed!",
+            ts_rank(t.search_vector, query) as "rank!",
+            ts_headline('english', t.title, query,
+                'StartSel="<mark>", StopSel="</mark>", HighlightAll=true') as "title_snippet!",
+            CASE WHEN t.description IS NULL THEN NULL
+                ELSE ts_headline('english', t.description, query,
+                    'StartSel="<mark>", StopSel="</mark>", MaxFragments=2, MaxWords=20, MinWords=5')
+            END as description_snippet
+        FROM todos t
+        CROSS JOIN websearch_to_tsquery('english', $1) query
+        CROSS JOIN LATERAL todo_subtask_progress(t.id) progress
+        WHERE t.search_vector @@ query AND t.deleted_at IS NULL
+        ORDER BY ts_rank(t.search_vector, query) DESC, t.created_at DESC
+        LIMIT $2
+        "#,
+        q,
+        limit
     )
     .fetch_all(db.pool())
     .await?;
 
-    Ok(Json(todos))
+    let hits = rows
+        .into_iter()
+        .map(|row| TodoSearchHit {
+            todo: Todo {
+                id: row.id,
+                parent_id: row.parent_id,
+                title: row.title,
+                description: row.description,
+                status: row.status,
+                cancellation_reason: row.cancellation_reason,
+                priority: row.priority,
+                due_at: row.due_at,
+                tags: row.tags,
+                owner_id: row.owner_id,
+                assignee_id: row.assignee_id,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                deleted_at: row.deleted_at,
+                version: row.version,
+                subtask_total: row.subtask_total,
+                subtask_completed: row.subtask_completed,
+            },
+            rank: row.rank,
+            title_snippet: row.title_snippet,
+            description_snippet: row.description_snippet,
+        })
+        .collect();
+
+    Ok(Json(hits))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/todos/{id}",
+    tag = "todos",
+    params(("id" = Uuid, Path, description = "Todo id"), DeletedQuery),
+    responses(
+        (status = 200, description = "The todo", body = Todo, headers(("ETag" = String, description = "Row version"))),
+        (status = 404, description = "No such todo", body = ApiError),
+    )
+)]
 pub async fn get_todo(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Todo>> {
-    let todo = sqlx::query_as!(
-        Todo,
-        r#"
-        SELECT 
-            id,
-            title,
-            description,
-            status as "status: TodoStatus",
-            created_at,
-            updated_at
-        FROM todos
-        WHERE id = $1
-        "#,
-        id
-    )
-    .fetch_optional(db.pool())
-    .await?
-    .ok_or(crate::error::AppError::NotFound)?;
+    Query(params): Query<DeletedQuery>,
+) -> Result<Tagged<Todo>> {
+    let todo = fetch_todo(db.pool(), id)
+        .await?
+        .filter(|todo| params.include_deleted || todo.deleted_at.is_none())
+        .ok_or(AppError::NotFound)?;
 
-    Ok(Json(todo))
+    Ok(Tagged(todo))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/todos",
+    tag = "todos",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when a request is retried with the same key")),
+    request_body = TodoCreate,
+    responses(
+        (status = 201, description = "Todo created", body = Todo),
+        (status = 400, description = "Unknown parent or assignee", body = ApiError),
+        (status = 409, description = "The initial status is not reachable, or a request with the same Idempotency-Key is running", body = ApiError),
+        (status = 422, description = "Invalid fields, or the Idempotency-Key was used for a different request", body = ApiError),
+        (status = 429, description = "Too many todos created; see `Retry-After`", body = ApiError),
+    )
+)]
 pub async fn create_todo(
     State(db): State<Database>,
-    Json(payload): Json<TodoCreate>,
+    CurrentUser(user): CurrentUser,
+    ValidJson(payload): ValidJson<TodoCreate>,
 ) -> Result<(StatusCode, Json<Todo>)> {
     let status = payload.status.unwrap_or(TodoStatus::Pending);
+    let priority = payload.priority.unwrap_or_default();
 
-    let todo = sqlx::query_as!(
-        Todo,
+    // New todos start as pending, so the initial status must be reachable from there
+    TodoStatus::Pending.check_transition(status, None)?;
+
+    let mut tx = db.pool().begin().await?;
+
+    if let Some(parent_id) = payload.parent_id {
+        ensure_live_parent(&mut tx, parent_id).await?;
+    }
+    if let Some(assignee_id) = payload.assignee_id {
+        ensure_user(&mut tx, assignee_id).await?;
+    }
+
+    let id = sqlx::query_scalar!(
         r#"
-        INSERT INTO todos (title, description, status)
-        VALUES ($1, $2, $3)
-        RETURNING 
-            id,
-            title,
-            description,
-            status as "status: TodoStatus",
-            created_at,
-            updated_at
+        INSERT INTO todos (title, description, status, priority, due_at, parent_id, owner_id, assignee_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id
         "#,
         payload.title,
         payload.description,
-        status as TodoStatus
+        status as TodoStatus,
+        priority as TodoPriority,
+        payload.due_at,
+        payload.parent_id,
+        user.id,
+        payload.assignee_id
     )
-    .fetch_one(db.pool())
+    .fetch_one(&mut *tx)
     .await?;
 
+    set_tags(&mut tx, &[id], &normalize_tags(&payload.tags)).await?;
+
+    let todo = fetch_todo(&mut *tx, id).await?.ok_or(AppError::NotFound)?;
+    record_events(&mut tx, TodoEventKind::Created, &[], std::slice::from_ref(&todo)).await?;
+    tx.commit().await?;
+
     Ok((StatusCode::CREATED, Json(todo)))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/todos/{id}",
+    tag = "todos",
+    params(
+        ("id" = Uuid, Path, description = "Todo id"),
+        ("If-Match" = Option<String>, Header, description = "Expected row version"),
+    ),
+    request_body = TodoUpdate,
+    responses(
+        (status = 200, description = "Updated todo", body = Todo, headers(("ETag" = String, description = "Row version"))),
+        (status = 404, description = "No such todo", body = ApiError),
+        (status = 409, description = "Status change not allowed", body = ApiError),
+        (status = 412, description = "The todo changed since `If-Match`", body = ApiError),
+        (status = 422, description = "Invalid fields, or cancelling without a reason", body = ApiError),
+    )
+)]
 pub async fn update_todo(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<TodoUpdate>,
-) -> Result<Json<Todo>> {
-    // First check if the todo exists
-    let _existing = sqlx::query!("SELECT id FROM todos WHERE id = $1", id)
-        .fetch_optional(db.pool())
-        .await?
-        .ok_or(crate::error::AppError::NotFound)?;
+    if_match: IfMatch,
+    ValidJson(payload): ValidJson<TodoUpdate>,
+) -> Result<Tagged<Todo>> {
+    let mut tx = db.pool().begin().await?;
 
-    // Build dynamic update query
-    let mut query = String::from("UPDATE todos SET updated_at = NOW()");
-    let mut params: Vec<String> = vec![];
-    let mut param_count = 1;
+    // Lock the row so the transition check and history diff see a stable version
+    sqlx::query!(
+        "SELECT id FROM todos WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    let before = fetch_todo(&mut *tx, id).await?.ok_or(AppError::NotFound)?;
+    if_match.check(before.version)?;
+    let current_status = before.status;
 
-    if let Some(title) = &payload.title {
-        query.push_str(&format!(", title = ${}", param_count));
-        params.push(title.clone());
-        param_count += 1;
+    if let Some(status) = payload.status {
+        current_status.check_transition(status, payload.status_reason.as_deref())?;
+    }
+    if let Some(assignee_id) = payload.assignee_id.value() {
+        ensure_user(&mut tx, *assignee_id).await?;
     }
 
-    if payload.description.is_some() {
-        query.push_str(&format!(", description = ${}", param_count));
-        params.push(payload.description.clone().unwrap_or_default());
-        param_count += 1;
+    // Tags go first so the row returned by the update already carries them
+    if let Some(tags) = &payload.tags {
+        set_tags(&mut tx, &[id], &normalize_tags(tags)).await?;
     }
 
-    if let Some(status) = &payload.status {
-        query.push_str(&format!(", status = ${}", param_count));
-        params.push(status.as_str().to_string());
-        param_count += 1;
+    let cancellation_reason = payload
+        .status
+        .filter(|status| *status != current_status && status.requires_reason())
+        .map(|_| payload.status_reason.unwrap_or_default().trim().to_string());
+
+    let mut update = UpdateBuilder::new("todos");
+    update
+        .set("title", payload.title)
+        .patch("description", payload.description)
+        .set("status", payload.status)
+        .set("cancellation_reason", cancellation_reason)
+        .set("priority", payload.priority)
+        .patch("due_at", payload.due_at)
+        .patch("assignee_id", payload.assignee_id);
+    let mut query = update.finish();
+    query.push("id = ").push_bind(id).push(TODO_RETURNING);
+    let todo = query.build_query_as::<Todo>().fetch_one(&mut *tx).await?;
+
+    record_events(
+        &mut tx,
+        TodoEventKind::Updated,
+        std::slice::from_ref(&before),
+        std::slice::from_ref(&todo),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Tagged(todo))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/todos/{id}",
+    tag = "todos",
+    params(("id" = Uuid, Path, description = "Todo id")),
+    responses(
+        (status = 204, description = "The todo and its subtasks moved to the trash"),
+        (status = 404, description = "No such todo", body = ApiError),
+    )
+)]
+pub async fn delete_todo(
+    State(db): State<Database>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let mut tx = db.pool().begin().await?;
+
+    let deleted = soft_delete_subtrees(&mut tx, &[id]).await?;
+    if deleted.is_empty() {
+        return Err(AppError::NotFound);
     }
 
-    query.push_str(&format!(" WHERE id = ${}", param_count));
+    tx.commit().await?;
 
-    // Execute update
-    let mut sqlx_query = sqlx::query(&query);
-    for param in params {
-        sqlx_query = sqlx_query.bind(param);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/todos/{id}/restore",
+    tag = "todos",
+    params(("id" = Uuid, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Restored todo", body = Todo),
+        (status = 400, description = "The todo is not in the trash", body = ApiError),
+        (status = 404, description = "No such todo", body = ApiError),
+        (status = 409, description = "The parent is still in the trash", body = ApiError),
+    )
+)]
+pub async fn restore_todo(
+    State(db): State<Database>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Todo>> {
+    let mut tx = db.pool().begin().await?;
+
+    let existing = fetch_todo(&mut *tx, id).await?.ok_or(AppError::NotFound)?;
+    if existing.deleted_at.is_none() {
+        return Err(AppError::BadRequest("Todo is not in the trash".to_string()));
+    }
+    if let Some(parent_id) = existing.parent_id {
+        let parent_live = sqlx::query_scalar!(
+            r#"SELECT deleted_at IS NULL as "live!" FROM todos WHERE id = $1"#,
+            parent_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if !parent_live {
+            return Err(AppError::Conflict(
+                "Restore the parent todo before its subtasks".to_string(),
+            ));
+        }
     }
-    sqlx_query = sqlx_query.bind(id);
-    sqlx_query.execute(db.pool()).await?;
 
-    // Fetch and return updated todo
-    let todo = sqlx::query_as!(
-        Todo,
+    // Subtasks deleted together with this todo share its deleted_at and come back with it
+    let ids = sqlx::query_scalar!(
         r#"
-        SELECT 
-            id,
-            title,
-            description,
-            status as "status: TodoStatus",
-            created_at,
-            updated_at
-        FROM todos
-        WHERE id = $1
+        WITH RECURSIVE subtree AS (
+            SELECT id, deleted_at FROM todos WHERE id = $1
+            UNION
+            SELECT t.id, t.deleted_at FROM todos t
+            JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at = s.deleted_at
+        )
+        SELECT id as "id!" FROM subtree
         "#,
         id
     )
-    .fetch_one(db.pool())
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let before = fetch_todos_by_ids(&mut *tx, &ids).await?;
+    sqlx::query!(
+        "UPDATE todos SET deleted_at = NULL, updated_at = NOW() WHERE id = ANY($1)",
+        &ids
+    )
+    .execute(&mut *tx)
     .await?;
+    let after = fetch_todos_by_ids(&mut *tx, &ids).await?;
+    record_events(&mut tx, TodoEventKind::Restored, &before, &after).await?;
+
+    let todo = fetch_todo(&mut *tx, id).await?.ok_or(AppError::NotFound)?;
+    tx.commit().await?;
 
     Ok(Json(todo))
 }
 
-pub async fn delete_todo(
+#[utoipa::path(
+    get,
+    path = "/api/v1/todos/{id}/children",
+    tag = "todos",
+    params(("id" = Uuid, Path, description = "Todo id")),
+    responses(
+        (status = 200, description = "Direct subtasks", body = Vec<Todo>),
+        (status = 404, description = "No such todo", body = ApiError),
+    )
+)]
+pub async fn list_children(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode> {
-    let result = sqlx::query!("DELETE FROM todos WHERE id = $1", id)
-        .execute(db.pool())
+) -> Result<Json<Vec<Todo>>> {
+    fetch_todo(db.pool(), id)
+        .await?
+        .filter(|todo| todo.deleted_at.is_none())
+        .ok_or(AppError::NotFound)?;
+
+    let child_ids = sqlx::query_scalar!(
+        "SELECT id FROM todos WHERE parent_id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_all(db.pool())
+    .await?;
+
+    let mut children = fetch_todos_by_ids(db.pool(), &child_ids).await?;
+    children.sort_by_key(|todo| todo.created_at);
+
+    Ok(Json(children))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/todos/{id}/parent",
+    tag = "todos",
+    params(
+        ("id" = Uuid, Path, description = "Todo id"),
+        ("If-Match" = Option<String>, Header, description = "Expected row version"),
+    ),
+    request_body = TodoMove,
+    responses(
+        (status = 200, description = "Moved todo", body = Todo, headers(("ETag" = String, description = "Row version"))),
+        (status = 400, description = "Unknown parent", body = ApiError),
+        (status = 404, description = "No such todo", body = ApiError),
+        (status = 412, description = "The todo changed since `If-Match`", body = ApiError),
+        (status = 422, description = "Would move the todo under itself", body = ApiError),
+    )
+)]
+pub async fn move_todo(
+    State(db): State<Database>,
+    Path(id): Path<Uuid>,
+    if_match: IfMatch,
+    Json(payload): Json<TodoMove>,
+) -> Result<Tagged<Todo>> {
+    let mut tx = db.pool().begin().await?;
+
+    // Serialize moves so two concurrent moves cannot together form a cycle
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext('todo_hierarchy'))")
+        .execute(&mut *tx)
         .await?;
 
-    if result.rows_affected() == 0 {
-        return Err(crate::error::AppError::NotFound);
+    sqlx::query!(
+        "SELECT id FROM todos WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+    let before = fetch_todo(&mut *tx, id).await?.ok_or(AppError::NotFound)?;
+    if_match.check(before.version)?;
+
+    if let Some(parent_id) = payload.parent_id {
+        ensure_live_parent(&mut tx, parent_id).await?;
+
+        // The new parent must not be the todo itself or one of its descendants
+        let creates_cycle = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT id, parent_id FROM todos WHERE id = $1
+                UNION
+                SELECT t.id, t.parent_id FROM todos t
+                JOIN ancestors a ON t.id = a.parent_id
+            )
+            SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = $2) as "exists!"
+            "#,
+            parent_id,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if creates_cycle {
+            return Err(AppError::UnprocessableEntity(
+                "A todo cannot be moved under itself or one of its subtasks".to_string(),
+            ));
+        }
     }
 
-    Ok(StatusCode::NO_CONTENT)
+    sqlx::query!(
+        "UPDATE todos SET parent_id = $1, updated_at = NOW() WHERE id = $2",
+        payload.parent_id,
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let todo = fetch_todo(&mut *tx, id).await?.ok_or(AppError::NotFound)?;
+    record_events(
+        &mut tx,
+        TodoEventKind::Updated,
+        std::slice::from_ref(&before),
+        std::slice::from_ref(&todo),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Tagged(todo))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/todos/{id}/history",
+    tag = "todos",
+    params(("id" = Uuid, Path, description = "Todo id")),
+    responses((status = 200, description = "Events, oldest first", body = Vec<TodoEvent>))
+)]
+pub async fn todo_history(
+    State(db): State<Database>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<TodoEvent>>> {
+    let events: Vec<TodoEvent> = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            todo_id,
+            kind as "kind: TodoEventKind",
+            changes as "changes: SqlJson<Vec<TodoFieldChange>>",
+            created_at
+        FROM todo_events
+        WHERE todo_id = $1
+        ORDER BY created_at, id
+        "#,
+        id
+    )
+    .fetch_all(db.pool())
+    .await?
+    .into_iter()
+    .map(|row| TodoEvent {
+        id: row.id,
+        todo_id: row.todo_id,
+        kind: row.kind,
+        changes: row.changes.0,
+        created_at: row.created_at,
+    })
+    .collect();
+
+    // Todos created before history existed have no events but are still valid
+    if events.is_empty() && fetch_todo(db.pool(), id).await?.is_none() {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(Json(events))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/todos/bulk",
+    tag = "todos",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when a request is retried with the same key")),
+    request_body = TodoBulkRequest,
+    responses(
+        (status = 200, description = "One result per requested id", body = TodoBulkResponse),
+        (status = 409, description = "A request with the same Idempotency-Key is running", body = ApiError),
+        (status = 422, description = "Invalid fields, or the Idempotency-Key was used for a different request", body = ApiError),
+    )
+)]
+pub async fn bulk_todos(
+    State(db): State<Database>,
+    ValidJson(payload): ValidJson<TodoBulkRequest>,
+) -> Result<Json<TodoBulkResponse>> {
+    let mut ids = payload.ids.clone();
+    ids.sort();
+    ids.dedup();
+
+    let mut tx = db.pool().begin().await?;
+
+    let mut outcomes: HashMap<Uuid, TodoBulkOutcome> = HashMap::new();
+    match payload.action {
+        TodoBulkAction::Delete => {
+            // Subtasks of the selected todos are trashed too but only the requested ids are reported
+            for id in soft_delete_subtrees(&mut tx, &ids).await? {
+                if ids.binary_search(&id).is_ok() {
+                    outcomes.insert(id, TodoBulkOutcome::Deleted);
+                }
+            }
+        }
+        TodoBulkAction::SetStatus { status, reason } => {
+            let update = TodoUpdate {
+                status: Some(status),
+                status_reason: reason,
+                ..Default::default()
+            };
+            bulk_update(&mut tx, &ids, update, &mut outcomes).await?;
+        }
+        TodoBulkAction::Edit(update) => {
+            if update.is_empty() {
+                return Err(AppError::BadRequest("No fields to update".to_string()));
+            }
+            bulk_update(&mut tx, &ids, update, &mut outcomes).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    let results = payload
+        .ids
+        .into_iter()
+        .map(|id| TodoBulkItemResult {
+            id,
+            outcome: outcomes
+                .get(&id)
+                .cloned()
+                .unwrap_or(TodoBulkOutcome::NotFound),
+        })
+        .collect();
+
+    Ok(Json(TodoBulkResponse { results }))
+}
+
+/// Applies the same field changes to every todo in `ids`; `None` leaves a field unchanged
+///
+/// Todos whose current status cannot move to `update.status` are left untouched
+/// and recorded as rejected.
+async fn bulk_update(
+    tx: &mut Transaction<'_, Postgres>,
+    ids: &[Uuid],
+    update: TodoUpdate,
+    outcomes: &mut HashMap<Uuid, TodoBulkOutcome>,
+) -> Result<()> {
+    let reason = update.status_reason.as_deref().map(str::trim);
+    let mut cancellation_reason = None;
+
+    let ids = match update.status {
+        Some(status) => {
+            // A missing reason applies to every item, so refuse the whole request
+            if status.requires_reason() && reason.is_none_or(str::is_empty) {
+                return Err(TransitionError::ReasonRequired.into());
+            }
+            if status.requires_reason() {
+                cancellation_reason = reason.map(str::to_string);
+            }
+
+            let current = sqlx::query!(
+                r#"
+                SELECT id, status as "status: TodoStatus"
+                FROM todos
+                WHERE id = ANY($1) AND deleted_at IS NULL
+                FOR UPDATE
+                "#,
+                ids
+            )
+            .fetch_all(&mut **tx)
+            .await?;
+
+            let mut allowed = Vec::with_capacity(current.len());
+            for row in current {
+                match row.status.check_transition(status, reason) {
+                    Ok(()) => allowed.push(row.id),
+                    Err(err) => {
+                        outcomes.insert(
+                            row.id,
+                            TodoBulkOutcome::Rejected {
+                                error: err.to_string(),
+                            },
+                        );
+                    }
+                }
+            }
+            allowed
+        }
+        None => ids.to_vec(),
+    };
+
+    let before = fetch_todos_by_ids(&mut **tx, &ids).await?;
+    let live_ids: Vec<Uuid> = before
+        .iter()
+        .filter(|todo| todo.deleted_at.is_none())
+        .map(|todo| todo.id)
+        .collect();
+
+    if let Some(assignee_id) = update.assignee_id.value() {
+        ensure_user(tx, *assignee_id).await?;
+    }
+    if let Some(tags) = &update.tags {
+        set_tags(tx, &live_ids, &normalize_tags(tags)).await?;
+    }
+
+    let mut builder = UpdateBuilder::new("todos");
+    builder
+        .set("title", update.title)
+        .patch("description", update.description)
+        .set("priority", update.priority)
+        .patch("due_at", update.due_at)
+        .patch("assignee_id", update.assignee_id);
+    if let Some(status) = update.status {
+        builder.set("status", Some(status));
+        // Todos already in the target status keep their original reason
+        if let Some(reason) = cancellation_reason {
+            builder
+                .assign("cancellation_reason")
+                .push("CASE WHEN status = ")
+                .push_bind(status)
+                .push(" THEN cancellation_reason ELSE ")
+                .push_bind(reason)
+                .push(" END");
+        }
+    }
+    let mut query = builder.finish();
+    query
+        .push("id = ANY(")
+        .push_bind(live_ids)
+        .push(") AND deleted_at IS NULL")
+        .push(TODO_RETURNING);
+    let after = query.build_query_as::<Todo>().fetch_all(&mut **tx).await?;
+
+    record_events(tx, TodoEventKind::Updated, &before, &after).await?;
+
+    for todo in after {
+        outcomes.insert(todo.id, TodoBulkOutcome::Updated { todo: Box::new(todo) });
+    }
+
+    Ok(())
+}
+
+/// Fails with a bad request unless `parent_id` is a todo that is not in the trash
+async fn ensure_live_parent(tx: &mut Transaction<'_, Postgres>, parent_id: Uuid) -> Result<()> {
+    let live = sqlx::query_scalar!(
+        "SELECT id FROM todos WHERE id = $1 AND deleted_at IS NULL",
+        parent_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .is_some();
+
+    if !live {
+        return Err(AppError::BadRequest("Parent todo not found".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Fails with a bad request unless `user_id` is an existing account
+async fn ensure_user(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<()> {
+    let exists = sqlx::query_scalar!("SELECT id FROM users WHERE id = $1", user_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .is_some();
+
+    if !exists {
+        return Err(AppError::BadRequest("Assignee not found".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Moves `ids` and all their live descendants to the trash, recording history
+///
+/// Returns every todo id that was deleted.
+async fn soft_delete_subtrees(
+    tx: &mut Transaction<'_, Postgres>,
+    ids: &[Uuid],
+) -> Result<Vec<Uuid>> {
+    let subtree = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id FROM todos WHERE id = ANY($1) AND deleted_at IS NULL
+            UNION
+            SELECT t.id FROM todos t
+            JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+        )
+        SELECT id as "id!" FROM subtree
+        "#,
+        ids
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    if subtree.is_empty() {
+        return Ok(subtree);
+    }
+
+    // Soft delete; the purge job removes the rows once the retention period has passed
+    let before = fetch_todos_by_ids(&mut **tx, &subtree).await?;
+    sqlx::query!(
+        "UPDATE todos SET deleted_at = NOW() WHERE id = ANY($1)",
+        &subtree
+    )
+    .execute(&mut **tx)
+    .await?;
+    let after = fetch_todos_by_ids(&mut **tx, &subtree).await?;
+    record_events(tx, TodoEventKind::Deleted, &before, &after).await?;
+
+    Ok(subtree)
+}
+
+/// Writes one history event per todo whose tracked fields differ between `before` and `after`
+///
+/// Only todos in `after` are recorded, matched to `before` by id; pass an empty
+/// `before` for creations.
+async fn record_events(
+    tx: &mut Transaction<'_, Postgres>,
+    kind: TodoEventKind,
+    before: &[Todo],
+    after: &[Todo],
+) -> Result<()> {
+    let before: HashMap<Uuid, &Todo> = before.iter().map(|todo| (todo.id, todo)).collect();
+
+    let mut todo_ids = Vec::new();
+    let mut changes = Vec::new();
+    for todo in after {
+        let diff = todo_changes(before.get(&todo.id).copied(), Some(todo));
+        if !diff.is_empty() {
+            todo_ids.push(todo.id);
+            changes.push(SqlJson(diff));
+        }
+    }
+
+    if todo_ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO todo_events (todo_id, kind, changes)
+        SELECT todo_id, $1, changes
+        FROM UNNEST($2::uuid[], $3::jsonb[]) AS e(todo_id, changes)
+        "#,
+        kind as TodoEventKind,
+        &todo_ids,
+        &changes as &[SqlJson<Vec<TodoFieldChange>>]
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Replaces the tag set of every todo in `todo_ids`, creating missing tags
+async fn set_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    todo_ids: &[Uuid],
+    tags: &[String],
+) -> Result<()> {
+    // Tags are only created for the todos they go on
+    if todo_ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO tags (name)
+        SELECT UNNEST($1::text[])
+        ON CONFLICT (name) DO NOTHING
+        "#,
+        tags
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!("DELETE FROM todo_tags WHERE todo_id = ANY($1)", todo_ids)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO todo_tags (todo_id, tag_id)
+        SELECT todo_id, tags.id
+        FROM UNNEST($1::uuid[]) AS todo_id
+        CROSS JOIN tags
+        WHERE tags.name = ANY($2)
+        "#,
+        todo_ids,
+        tags
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// RETURNING clause that yields a full [`Todo`] from an update of `todos`
+const TODO_RETURNING: &str = r#"
+    RETURNING
+        id,
+        parent_id,
+        title,
+        description,
+        status,
+        cancellation_reason,
+        priority,
+        due_at,
+        ARRAY(
+            SELECT tags.name FROM todo_tags
+            JOIN tags ON tags.id = todo_tags.tag_id
+            WHERE todo_tags.todo_id = todos.id
+            ORDER BY tags.name
+        ) as tags,
+        owner_id,
+        assignee_id,
+        created_at,
+        updated_at,
+        deleted_at,
+        version,
+        (SELECT total FROM todo_subtask_progress(todos.id)) as subtask_total,
+        (SELECT completed FROM todo_subtask_progress(todos.id)) as subtask_completed
+"#;
+
+async fn fetch_todo<'e, E>(executor: E, id: Uuid) -> Result<Option<Todo>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    Ok(fetch_todos_by_ids(executor, &[id]).await?.pop())
+}
+
+async fn fetch_todos_by_ids<'e, E>(executor: E, ids: &[Uuid]) -> Result<Vec<Todo>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let todos = sqlx::query_as!(
+        Todo,
+        r#"
+        SELECT
+            id,
+            parent_id,
+            title,
+            description,
+            status as "status: TodoStatus",
+            cancellation_reason,
+            priority as "priority: TodoPriority",
+            due_at,
+            ARRAY(
+                SELECT tags.name FROM todo_tags
+                JOIN tags ON tags.id = todo_tags.tag_id
+                WHERE todo_tags.todo_id = todos.id
+                ORDER BY tags.name
+            ) as "tags!",
+            owner_id,
+            assignee_id,
+            created_at,
+            updated_at,
+            deleted_at,
+            version,
+            progress.total as "subtask_total!",
+            progress.completed as "subtask_completed!"
+        FROM todos
+        CROSS JOIN LATERAL todo_subtask_progress(todos.id) progress
+        WHERE id = ANY($1)
+        "#,
+        ids
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(todos)
+}
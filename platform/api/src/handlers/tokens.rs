@@ -0,0 +1,173 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use platform_core::{ApiError, ApiToken, ApiTokenCreate, ApiTokenCreated, FieldError, API_TOKEN_PREFIX};
+use uuid::Uuid;
+
+use crate::{
+    auth::{generate_token, hash_token, parse_scopes, Credential, CurrentUser},
+    db::Database,
+    error::{AppError, Result},
+    validation::ValidJson,
+};
+
+/// Characters of the secret after [`API_TOKEN_PREFIX`] kept for display
+const DISPLAY_PREFIX_LEN: usize = 6;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens",
+    tag = "tokens",
+    responses((status = 200, description = "The caller's tokens, newest first", body = Vec<ApiToken>))
+)]
+pub async fn list_tokens(
+    State(db): State<Database>,
+    CurrentUser(user): CurrentUser,
+) -> Result<Json<Vec<ApiToken>>> {
+    let tokens = sqlx::query!(
+        r#"
+        SELECT id, name, prefix, scopes, created_at, last_used_at, expires_at
+        FROM api_tokens
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user.id
+    )
+    .fetch_all(db.pool())
+    .await?
+    .into_iter()
+    .map(|row| ApiToken {
+        id: row.id,
+        name: row.name,
+        prefix: row.prefix,
+        scopes: parse_scopes(&row.scopes),
+        created_at: row.created_at,
+        last_used_at: row.last_used_at,
+        expires_at: row.expires_at,
+    })
+    .collect();
+
+    Ok(Json(tokens))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/tokens",
+    tag = "tokens",
+    request_body = ApiTokenCreate,
+    responses(
+        (status = 201, description = "Token created; `secret` is only returned here", body = ApiTokenCreated),
+        (status = 403, description = "Called with an API token instead of a session", body = ApiError),
+        (status = 422, description = "Invalid fields, or scopes beyond the caller's role", body = ApiError),
+    )
+)]
+pub async fn create_token(
+    State(db): State<Database>,
+    CurrentUser(user): CurrentUser,
+    Extension(credential): Extension<Credential>,
+    ValidJson(payload): ValidJson<ApiTokenCreate>,
+) -> Result<(StatusCode, Json<ApiTokenCreated>)> {
+    require_session(&credential)?;
+
+    let name = payload.name.trim();
+    let mut scopes = payload.scopes;
+    scopes.sort_by_key(|scope| scope.as_str());
+    scopes.dedup();
+    // A token can never do more than the account that made it
+    let beyond_role: Vec<FieldError> = scopes
+        .iter()
+        .filter(|scope| !user.can(**scope))
+        .map(|scope| {
+            FieldError::new(
+                "scopes",
+                format!("{} needs the {} role", scope, scope.min_role()),
+            )
+        })
+        .collect();
+    if !beyond_role.is_empty() {
+        return Err(AppError::Validation(beyond_role));
+    }
+
+    let expires_at: Option<DateTime<Utc>> = payload
+        .expires_in_days
+        .map(|days| Utc::now() + chrono::Duration::days(days.into()));
+
+    let secret = format!("{}{}", API_TOKEN_PREFIX, generate_token());
+    let prefix = secret[..API_TOKEN_PREFIX.len() + DISPLAY_PREFIX_LEN].to_string();
+    let scope_names: Vec<String> = scopes.iter().map(|scope| scope.to_string()).collect();
+
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO api_tokens (user_id, name, prefix, token_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, created_at
+        "#,
+        user.id,
+        name,
+        prefix,
+        hash_token(&secret),
+        &scope_names,
+        expires_at
+    )
+    .fetch_one(db.pool())
+    .await?;
+
+    let token = ApiToken {
+        id: row.id,
+        name: name.to_string(),
+        prefix,
+        scopes,
+        created_at: row.created_at,
+        last_used_at: None,
+        expires_at,
+    };
+
+    Ok((StatusCode::CREATED, Json(ApiTokenCreated { token, secret })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tokens/{id}",
+    tag = "tokens",
+    params(("id" = Uuid, Path, description = "Token id")),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 403, description = "Called with an API token instead of a session", body = ApiError),
+        (status = 404, description = "No such token of the caller's", body = ApiError),
+    )
+)]
+pub async fn revoke_token(
+    State(db): State<Database>,
+    CurrentUser(user): CurrentUser,
+    Extension(credential): Extension<Credential>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    require_session(&credential)?;
+
+    let result = sqlx::query!(
+        "DELETE FROM api_tokens WHERE id = $1 AND user_id = $2",
+        id,
+        user.id
+    )
+    .execute(db.pool())
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Tokens are managed from a signed-in session, so a leaked token cannot mint more
+fn require_session(credential: &Credential) -> Result<()> {
+    match credential {
+        Credential::Session => Ok(()),
+        Credential::ApiToken { .. } => Err(AppError::Forbidden(
+            "API tokens cannot create or revoke tokens; sign in instead".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,153 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use platform_core::{ApiError, Assignee, User, UserCreate, UserRole, UserRoleUpdate};
+use uuid::Uuid;
+
+use crate::{
+    auth::hash_password,
+    db::Database,
+    error::{AppError, Result},
+    validation::ValidJson,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    tag = "users",
+    responses((status = 200, description = "Every account, with emails and roles", body = Vec<User>))
+)]
+pub async fn list_users(State(db): State<Database>) -> Result<Json<Vec<User>>> {
+    let users = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, email, display_name, role as "role: UserRole", created_at
+        FROM users
+        ORDER BY created_at
+        "#
+    )
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(Json(users))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/assignees",
+    tag = "users",
+    responses((status = 200, description = "Every account's id and name, for showing and picking assignees", body = Vec<Assignee>))
+)]
+pub async fn list_assignees(State(db): State<Database>) -> Result<Json<Vec<Assignee>>> {
+    let assignees = sqlx::query_as!(
+        Assignee,
+        "SELECT id, display_name FROM users ORDER BY display_name"
+    )
+    .fetch_all(db.pool())
+    .await?;
+
+    Ok(Json(assignees))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/users",
+    tag = "users",
+    request_body = UserCreate,
+    responses(
+        (status = 201, description = "Account created", body = User),
+        (status = 409, description = "The email is taken", body = ApiError),
+        (status = 422, description = "Invalid fields", body = ApiError),
+    )
+)]
+pub async fn create_user(
+    State(db): State<Database>,
+    ValidJson(payload): ValidJson<UserCreate>,
+) -> Result<(StatusCode, Json<User>)> {
+    let email = payload.email.trim();
+    let display_name = payload.display_name.trim();
+
+    let password_hash = hash_password(payload.password).await?;
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        INSERT INTO users (email, display_name, password_hash, role)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, email, display_name, role as "role: UserRole", created_at
+        "#,
+        email,
+        display_name,
+        password_hash,
+        payload.role as UserRole
+    )
+    .fetch_one(db.pool())
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/users/{id}/role",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "User id")),
+    request_body = UserRoleUpdate,
+    responses(
+        (status = 200, description = "Role changed", body = User),
+        (status = 404, description = "No such user", body = ApiError),
+        (status = 409, description = "Would demote the last admin", body = ApiError),
+    )
+)]
+pub async fn update_user_role(
+    State(db): State<Database>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UserRoleUpdate>,
+) -> Result<Json<User>> {
+    let mut tx = db.pool().begin().await?;
+
+    // Serialize role changes so two demotions cannot both see another admin left
+    sqlx::query!("LOCK TABLE users IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await?;
+
+    let current = sqlx::query_scalar!(
+        r#"SELECT role as "role: UserRole" FROM users WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    if current == UserRole::Admin && payload.role != UserRole::Admin {
+        let admins = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM users WHERE role = 'admin'"#
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if admins <= 1 {
+            return Err(AppError::Conflict(
+                "Cannot remove the last admin; promote another user first".to_string(),
+            ));
+        }
+    }
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        UPDATE users
+        SET role = $2, updated_at = NOW()
+        WHERE id = $1
+        RETURNING id, email, display_name, role as "role: UserRole", created_at
+        "#,
+        id,
+        payload.role as UserRole
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(user))
+}
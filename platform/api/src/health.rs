@@ -0,0 +1,184 @@
+//! Liveness and readiness probes
+//!
+//! These routes sit outside the error and timeout layers so a failing probe
+//! keeps its own body, and are never behind authentication.
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::db::Database;
+
+/// Longest a readiness check waits on the database before reporting it down
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Unavailable,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Liveness {
+    pub status: CheckStatus,
+}
+
+/// Whether the server can take traffic, with one entry per dependency
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Readiness {
+    /// `ok` only when every check is
+    pub status: CheckStatus,
+    pub checks: ReadinessChecks,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessChecks {
+    pub database: DatabaseCheck,
+    pub migrations: MigrationsCheck,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DatabaseCheck {
+    pub status: CheckStatus,
+    /// Round trip of `SELECT 1`, including waiting for a pooled connection
+    pub latency_ms: f64,
+    /// Open connections in the pool
+    pub pool_size: u32,
+    pub pool_idle: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MigrationsCheck {
+    pub status: CheckStatus,
+    /// Migrations shipped with this build
+    pub expected: usize,
+    /// Versions of shipped migrations the database has not applied
+    pub pending: Vec<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    security(()),
+    responses((status = 200, description = "The process is up; same as `/health/live`", body = String))
+)]
+pub async fn health_check() -> &'static str {
+    "OK"
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    security(()),
+    responses((status = 200, description = "The process is up and serving requests", body = Liveness))
+)]
+pub async fn live() -> Json<Liveness> {
+    Json(Liveness {
+        status: CheckStatus::Ok,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "The database is reachable and fully migrated", body = Readiness),
+        (status = 503, description = "A check failed; see `checks`", body = Readiness),
+    )
+)]
+pub async fn ready(State(db): State<Database>) -> (StatusCode, Json<Readiness>) {
+    let database = check_database(&db).await;
+    let migrations = check_migrations(&db).await;
+
+    let status = if database.status == CheckStatus::Ok && migrations.status == CheckStatus::Ok {
+        CheckStatus::Ok
+    } else {
+        CheckStatus::Unavailable
+    };
+    let code = match status {
+        CheckStatus::Ok => StatusCode::OK,
+        CheckStatus::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (
+        code,
+        Json(Readiness {
+            status,
+            checks: ReadinessChecks {
+                database,
+                migrations,
+            },
+        }),
+    )
+}
+
+async fn check_database(db: &Database) -> DatabaseCheck {
+    let started = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, sqlx::query("SELECT 1").execute(db.pool())).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => {
+            tracing::warn!("Readiness: database query failed: {}", e);
+            Some("query failed".to_string())
+        }
+        Err(_) => {
+            tracing::warn!("Readiness: database did not answer within {:?}", CHECK_TIMEOUT);
+            Some("timed out".to_string())
+        }
+    };
+
+    DatabaseCheck {
+        status: if error.is_none() {
+            CheckStatus::Ok
+        } else {
+            CheckStatus::Unavailable
+        },
+        latency_ms,
+        pool_size: db.pool().size(),
+        pool_idle: db.pool().num_idle(),
+        error,
+    }
+}
+
+async fn check_migrations(db: &Database) -> MigrationsCheck {
+    let expected = db.migration_count();
+    match tokio::time::timeout(CHECK_TIMEOUT, db.pending_migrations()).await {
+        Ok(Ok(pending)) => MigrationsCheck {
+            status: if pending.is_empty() {
+                CheckStatus::Ok
+            } else {
+                CheckStatus::Unavailable
+            },
+            expected,
+            pending,
+            error: None,
+        },
+        Ok(Err(e)) => {
+            tracing::warn!("Readiness: cannot read migration state: {}", e);
+            MigrationsCheck {
+                status: CheckStatus::Unavailable,
+                expected,
+                pending: Vec::new(),
+                error: Some("cannot read migration state".to_string()),
+            }
+        }
+        Err(_) => MigrationsCheck {
+            status: CheckStatus::Unavailable,
+            expected,
+            pending: Vec::new(),
+            error: Some("timed out".to_string()),
+        },
+    }
+}
@@ -0,0 +1,329 @@
+//! `Idempotency-Key` support for POST routes that create records
+//!
+//! The first request with a key claims it and runs; its response is stored
+//! for the user and replayed for any retry with the same key and request, so
+//! a retried create never makes a second record. Server errors are not
+//! stored, and free the key for another attempt. So does a request dropped
+//! before it answered, by a disconnect or the request timeout, once the
+//! timeout has passed since it claimed the key.
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{FromRequest, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use platform_core::ApiError;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    auth::CurrentUser,
+    error::{AppError, Result},
+    state::AppState,
+};
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+/// Set on replayed responses
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const MAX_KEY_LEN: usize = 255;
+
+/// Response headers stored and replayed along with the body
+const STORED_HEADERS: [HeaderName; 3] = [header::CONTENT_TYPE, header::ETAG, header::LOCATION];
+
+/// Method route layer replaying the stored response for a repeated `Idempotency-Key`
+///
+/// Requests without the header run as usual. Must run inside the
+/// authentication layer, since keys belong to the signed-in user.
+pub async fn idempotent(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    let Some(key) = request.headers().get(&IDEMPOTENCY_KEY) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LEN)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters",
+                MAX_KEY_LEN
+            ))
+        })?
+        .to_string();
+    let Some(CurrentUser(user)) = request.extensions().get::<CurrentUser>().cloned() else {
+        return Ok(next.run(request).await);
+    };
+
+    let (parts, body) = request.into_parts();
+    // Through the extractor so the route's body limit still applies
+    let body = match Bytes::from_request(Request::from_parts(parts.clone(), body), &()).await {
+        Ok(body) => body,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
+    let hash = request_hash(&parts.method, parts.uri.path(), &body);
+
+    let Some(claimed_at) = claim(&state, user.id, &key, &hash).await? else {
+        return replay(&state, user.id, &key, &hash).await;
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if response.status().is_server_error() {
+        release(&state, user.id, &key, claimed_at).await;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read the response to store: {}", e)))?;
+    let headers: Map<String, Value> = STORED_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = parts.headers.get(name)?.to_str().ok()?;
+            Some((name.to_string(), Value::String(value.to_string())))
+        })
+        .collect();
+
+    // Only while the claim is still ours
+    sqlx::query!(
+        r#"
+        UPDATE idempotency_keys
+        SET response_status = $4, response_headers = $5, response_body = $6
+        WHERE user_id = $1 AND key = $2 AND claimed_at = $3
+        "#,
+        user.id,
+        key,
+        claimed_at,
+        parts.status.as_u16() as i16,
+        Value::Object(headers),
+        body.as_ref()
+    )
+    .execute(state.db.pool())
+    .await?;
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// SHA-256 of the method, path and body, telling a retry from a different request
+fn request_hash(method: &Method, path: &str, body: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b" ");
+    hasher.update(path);
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher.finalize().to_vec()
+}
+
+/// Records the key as in progress, returning when it was claimed; `None` if
+/// another request holds it or its response is stored
+async fn claim(
+    state: &AppState,
+    user_id: Uuid,
+    key: &str,
+    hash: &[u8],
+) -> Result<Option<DateTime<Utc>>> {
+    let expires_at = Utc::now() + state.idempotency_ttl;
+    // Expired rows, and claims whose request was dropped without answering,
+    // are taken over as if they were not there
+    let claimed_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO idempotency_keys (user_id, key, request_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, key) DO UPDATE
+        SET request_hash = EXCLUDED.request_hash,
+            response_status = NULL,
+            response_headers = NULL,
+            response_body = NULL,
+            claimed_at = NOW(),
+            expires_at = EXCLUDED.expires_at
+        WHERE idempotency_keys.expires_at <= NOW()
+           OR (idempotency_keys.response_status IS NULL
+               AND idempotency_keys.claimed_at <= NOW() - make_interval(secs => $5))
+        RETURNING claimed_at
+        "#,
+        user_id,
+        key,
+        hash,
+        expires_at,
+        state.request_timeout.as_secs_f64()
+    )
+    .fetch_optional(state.db.pool())
+    .await?;
+
+    Ok(claimed_at)
+}
+
+/// Frees the key after a server error so the client can try again
+async fn release(state: &AppState, user_id: Uuid, key: &str, claimed_at: DateTime<Utc>) {
+    let result = sqlx::query!(
+        "DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2 AND claimed_at = $3",
+        user_id,
+        key,
+        claimed_at
+    )
+    .execute(state.db.pool())
+    .await;
+    if let Err(e) = result {
+        tracing::error!("Failed to release Idempotency-Key {:?}: {}", key, e);
+    }
+}
+
+/// The stored response for a key that is already taken
+async fn replay(state: &AppState, user_id: Uuid, key: &str, hash: &[u8]) -> Result<Response> {
+    let stored = sqlx::query!(
+        r#"
+        SELECT request_hash, response_status, response_headers, response_body
+        FROM idempotency_keys
+        WHERE user_id = $1 AND key = $2
+        "#,
+        user_id,
+        key
+    )
+    .fetch_optional(state.db.pool())
+    .await?
+    // Released by a failed first attempt since we tried to claim it
+    .ok_or_else(|| {
+        AppError::Conflict("The request with this Idempotency-Key failed; try again".to_string())
+    })?;
+
+    if stored.request_hash != hash {
+        return Err(AppError::UnprocessableEntity(
+            "This Idempotency-Key was already used for a different request".to_string(),
+        ));
+    }
+    let (Some(status), Some(body)) = (stored.response_status, stored.response_body) else {
+        return Err(AppError::Conflict(
+            "A request with this Idempotency-Key is still in progress".to_string(),
+        ));
+    };
+
+    let status = StatusCode::from_u16(status as u16)
+        .map_err(|e| AppError::Internal(format!("Stored response has a bad status: {}", e)))?;
+    let mut headers = HeaderMap::new();
+    if let Some(Value::Object(stored_headers)) = stored.response_headers {
+        for (name, value) in stored_headers {
+            if let (Ok(name), Some(Ok(value))) = (
+                HeaderName::try_from(name),
+                value.as_str().map(HeaderValue::from_str),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+    // Lets `finish_errors` render a replayed error like a fresh one
+    let error = status
+        .is_client_error()
+        .then(|| serde_json::from_slice::<ApiError>(&body).ok())
+        .flatten();
+    let mut response = (status, headers, body).into_response();
+    if let Some(error) = error {
+        response.extensions_mut().insert(error);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{idempotent, IDEMPOTENCY_KEY};
+    use crate::{auth::CurrentUser, db::Database, test_support};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        middleware,
+        routing::post,
+        Router,
+    };
+    use chrono::Utc;
+    use platform_core::{User, UserRole};
+    use sqlx::PgPool;
+    use std::{
+        future,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use tower::Service;
+
+    const REQUEST_TIMEOUT: Duration = Duration::from_millis(200);
+
+    async fn user(pool: &PgPool) -> User {
+        let id = sqlx::query_scalar(
+            "INSERT INTO users (email, display_name, password_hash) VALUES ($1, $1, '') RETURNING id",
+        )
+        .bind("retry@example.com")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        User {
+            id,
+            email: "retry@example.com".to_string(),
+            display_name: "retry@example.com".to_string(),
+            role: UserRole::Viewer,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// A create route whose first request never answers, behind [`idempotent`]
+    fn app(pool: PgPool) -> Router {
+        let mut config = test_support::config();
+        config.request_timeout = REQUEST_TIMEOUT;
+        let state = test_support::state(&config, Database::from_pool(pool));
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let create = move || async move {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                future::pending::<()>().await;
+            }
+            StatusCode::CREATED
+        };
+        Router::new()
+            .route("/things", post(create))
+            .layer(middleware::from_fn_with_state(state, idempotent))
+    }
+
+    fn create(user: &User) -> Request<Body> {
+        let mut request = Request::post("/things")
+            .header(IDEMPOTENCY_KEY, "retry-1")
+            .body(Body::from("{}"))
+            .unwrap();
+        request.extensions_mut().insert(CurrentUser(user.clone()));
+        request
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn abandoned_claim_is_taken_over_after_the_request_timeout(pool: PgPool) {
+        let user = user(&pool).await;
+        let mut app = app(pool);
+
+        // The client gives up on the first request, which is dropped unanswered
+        let first = tokio::time::timeout(Duration::from_millis(50), app.call(create(&user))).await;
+        assert!(first.is_err(), "the first request answered");
+
+        // It may still be running, so retries wait for it
+        let retry = app.call(create(&user)).await.unwrap();
+        assert_eq!(retry.status(), StatusCode::CONFLICT);
+
+        // After the timeout it cannot be, and the next retry runs
+        tokio::time::sleep(REQUEST_TIMEOUT).await;
+        let retry = app.call(create(&user)).await.unwrap();
+        assert_eq!(retry.status(), StatusCode::CREATED);
+
+        // Its response is the one stored and replayed from now on
+        let replay = app.call(create(&user)).await.unwrap();
+        assert_eq!(replay.status(), StatusCode::CREATED);
+        assert_eq!(replay.headers()["idempotent-replayed"], "true");
+    }
+}
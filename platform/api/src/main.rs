@@ -1,51 +1,164 @@
+mod auth;
 mod config;
 mod db;
 mod error;
+mod etag;
+mod events;
 mod handlers;
+mod health;
+mod idempotency;
+mod metrics;
+mod openapi;
+mod purge;
+mod rate_limit;
 mod routes;
+mod shutdown;
+mod state;
+mod telemetry;
+#[cfg(test)]
+mod test_support;
+mod update;
+mod validation;
 
 use crate::config::Config;
 use crate::db::Database;
+use crate::events::EventBus;
+use crate::metrics::Metrics;
+use crate::rate_limit::RateLimiter;
 use crate::routes::create_router;
+use crate::state::AppState;
+use axum::http::{header, HeaderName};
+use axum_server::{tls_rustls::RustlsConfig, Handle};
 use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "platform_api=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    // Load configuration
+    // Load configuration, then start logging in the configured format
     let config = Config::from_env()?;
+    telemetry::init(config.log_format);
+    config.log_summary();
 
     // Connect to database and run migrations
-    let db = Database::new(&config.database_url).await?;
+    let db = Database::new(
+        &config.database_url,
+        config.database_max_connections,
+        config.database_acquire_timeout,
+        config.slow_query_threshold,
+    )
+    .await?;
     db.run_migrations().await?;
 
     tracing::info!("Database migrations completed successfully");
 
+    // Make sure there is someone who can sign in
+    auth::bootstrap_admin(
+        &db,
+        config.admin_email.as_deref(),
+        config.admin_password.as_deref(),
+    )
+    .await
+    .map_err(|e| format!("Failed to create the initial account: {:?}", e))?;
+
+    // Permanently remove rows that have been in the trash longer than the retention period
+    purge::spawn(db.clone(), config.trash_retention_days);
+
+    // Forward database change notifications to the live change feeds
+    let events = EventBus::new();
+    events::spawn_listener(db.clone(), events.clone());
+
     // Setup CORS
+    let origins = if config.allows_any_origin() {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            config
+                .cors_allowed_origins
+                .iter()
+                .filter_map(|origin| origin.parse().ok()),
+        )
+    };
+    // Listed origins may send the session cookie, which the change feed relies
+    // on; browsers refuse credentials with wildcards, hence the mirroring
     let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_origin(origins)
+        .allow_credentials(!config.allows_any_origin())
+        .allow_methods(AllowMethods::mirror_request())
+        .allow_headers(AllowHeaders::mirror_request())
+        // Let the browser client read row versions for If-Match, request ids,
+        // deprecation notices and idempotent replays
+        .expose_headers([
+            header::ETAG,
+            HeaderName::from_static("x-request-id"),
+            HeaderName::from_static("deprecation"),
+            HeaderName::from_static("sunset"),
+            header::LINK,
+            idempotency::IDEMPOTENT_REPLAYED,
+        ]);
 
     // Create router
-    let app = create_router(db).layer(cors);
+    let state = AppState {
+        db: db.clone(),
+        session_ttl: chrono::Duration::hours(config.session_ttl_hours),
+        secure_cookies: config.secure_cookies,
+        idempotency_ttl: chrono::Duration::hours(config.idempotency_ttl_hours),
+        request_timeout: config.request_timeout,
+        metrics: Metrics::new(),
+        rate_limiter: RateLimiter::new(&config),
+        events: events.clone(),
+    };
+    let app = create_router(state, &config).layer(cors);
+
+    // Start server; on SIGTERM stop accepting connections and let in-flight
+    // requests finish for up to `shutdown_timeout`
+    let addr = config.addr();
+    let shutdown_timeout = config.shutdown_timeout;
+    match &config.tls {
+        Some(tls) => {
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let rustls = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+                .await
+                .map_err(|e| format!("Failed to load the TLS certificate: {}", e))?;
+
+            let handle = Handle::new();
+            tokio::spawn({
+                let handle = handle.clone();
+                let events = events.clone();
+                async move {
+                    shutdown::signal().await;
+                    tracing::info!("Shutting down; waiting up to {:?} for requests", shutdown_timeout);
+                    events.close();
+                    handle.graceful_shutdown(Some(shutdown_timeout));
+                }
+            });
 
-    // Start server
-    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
-    tracing::info!("Starting server on {}", addr);
+            tracing::info!("Starting server on https://{}", addr);
+            axum_server::bind_rustls(addr, rustls)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await?;
+        }
+        None => {
+            tracing::info!("Starting server on http://{}", addr);
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            let app = app.into_make_service_with_connect_info::<SocketAddr>();
+            let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+                shutdown::signal().await;
+                tracing::info!("Shutting down; waiting up to {:?} for requests", shutdown_timeout);
+                events.close();
+            });
+            // Draining has no limit of its own
+            tokio::select! {
+                result = server => result?,
+                _ = shutdown::deadline(shutdown_timeout) => {
+                    tracing::warn!("Requests still running after {:?} were dropped", shutdown_timeout);
+                }
+            }
+        }
+    }
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    db.pool().close().await;
+    tracing::info!("Server stopped");
 
     Ok(())
 }
@@ -0,0 +1,243 @@
+//! Prometheus metrics served at `/metrics`
+//!
+//! Request metrics are recorded by [`track`] as requests finish; database and
+//! todo gauges are refreshed on every scrape.
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use platform_core::TodoStatus;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::time::{Duration, Instant};
+
+use crate::{db::Database, state::AppState};
+
+/// Route label for requests that matched no route, so unknown paths do not
+/// each get their own series
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Latency buckets in seconds, from 5ms to 10s
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+/// Every metric the API exports; cheap to clone
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    db_pool_connections: IntGaugeVec,
+    todos: IntGaugeVec,
+    theme_activations: IntCounter,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Finished HTTP requests"),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time from receiving a request to sending the response head",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+            &["method", "route"],
+        )
+        .expect("valid metric");
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Database pool connections: idle, active (checked out) and max",
+            ),
+            &["state"],
+        )
+        .expect("valid metric");
+        let todos = IntGaugeVec::new(
+            Opts::new("todos", "Todos outside the trash, by status"),
+            &["status"],
+        )
+        .expect("valid metric");
+        let theme_activations = IntCounter::new(
+            "theme_activations_total",
+            "Times a theme was made the active theme",
+        )
+        .expect("valid metric");
+
+        let registry = Registry::new_custom(Some("atom".to_string()), None).expect("valid prefix");
+        registry
+            .register(Box::new(http_requests.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(http_request_duration.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(db_pool_connections.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(todos.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(theme_activations.clone()))
+            .expect("metric registered once");
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            db_pool_connections,
+            todos,
+            theme_activations,
+        }
+    }
+
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, latency: Duration) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn theme_activated(&self) {
+        self.theme_activations.inc();
+    }
+
+    pub fn set_pool(&self, size: u32, idle: usize, max: u32) {
+        let idle = idle as i64;
+        self.db_pool_connections.with_label_values(&["idle"]).set(idle);
+        self.db_pool_connections
+            .with_label_values(&["active"])
+            .set(size as i64 - idle);
+        self.db_pool_connections
+            .with_label_values(&["max"])
+            .set(max as i64);
+    }
+
+    /// Sets the todo gauge for every status, zero for those missing from `counts`
+    pub fn set_todo_counts(&self, counts: &[(TodoStatus, i64)]) {
+        for status in TodoStatus::ALL {
+            let count = counts
+                .iter()
+                .find(|(counted, _)| *counted == status)
+                .map_or(0, |(_, count)| *count);
+            self.todos.with_label_values(&[status.as_str()]).set(count);
+        }
+    }
+
+    /// Everything in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding cannot fail");
+        String::from_utf8(buffer).expect("metrics are UTF-8")
+    }
+
+    /// Reads the pool state and todo counts from `db`
+    async fn refresh(&self, db: &Database) {
+        let pool = db.pool();
+        self.set_pool(pool.size(), pool.num_idle(), pool.options().get_max_connections());
+
+        let counts = sqlx::query!(
+            r#"SELECT status as "status!: TodoStatus", COUNT(*) as "count!" FROM todos WHERE deleted_at IS NULL GROUP BY status"#
+        )
+        .fetch_all(pool)
+        .await;
+        match counts {
+            Ok(rows) => {
+                let counts: Vec<_> = rows.into_iter().map(|row| (row.status, row.count)).collect();
+                self.set_todo_counts(&counts);
+            }
+            // Keep the last counts; the scrape still reports everything else
+            Err(e) => tracing::warn!("Failed to count todos for metrics: {}", e),
+        }
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Middleware recording every finished request under its matched route
+pub async fn track(State(metrics): State<Metrics>, request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE.to_string(), |path| path.as_str().to_string());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    metrics.observe_request(&method, &route, response.status().as_u16(), started.elapsed());
+    response
+}
+
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    state.metrics.refresh(&state.db).await;
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        state.metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metrics;
+    use platform_core::TodoStatus;
+    use std::time::Duration;
+
+    #[test]
+    fn renders_the_text_exposition_format() {
+        let metrics = Metrics::new();
+        metrics.observe_request("GET", "/api/v1/todos", 200, Duration::from_millis(30));
+        metrics.observe_request("GET", "/api/v1/todos", 200, Duration::from_millis(3));
+        metrics.theme_activated();
+        metrics.set_pool(4, 3, 5);
+        metrics.set_todo_counts(&[(TodoStatus::Pending, 7)]);
+
+        let text = metrics.render();
+        let lines: Vec<&str> = text.lines().collect();
+        for expected in [
+            "# HELP atom_http_requests_total Finished HTTP requests",
+            "# TYPE atom_http_requests_total counter",
+            r#"atom_http_requests_total{method="GET",route="/api/v1/todos",status="200"} 2"#,
+            "# TYPE atom_http_request_duration_seconds histogram",
+            r#"atom_http_request_duration_seconds_bucket{method="GET",route="/api/v1/todos",le="0.005"} 1"#,
+            r#"atom_http_request_duration_seconds_bucket{method="GET",route="/api/v1/todos",le="0.05"} 2"#,
+            r#"atom_http_request_duration_seconds_bucket{method="GET",route="/api/v1/todos",le="+Inf"} 2"#,
+            r#"atom_http_request_duration_seconds_count{method="GET",route="/api/v1/todos"} 2"#,
+            "# TYPE atom_db_pool_connections gauge",
+            r#"atom_db_pool_connections{state="active"} 1"#,
+            r#"atom_db_pool_connections{state="idle"} 3"#,
+            r#"atom_db_pool_connections{state="max"} 5"#,
+            "# TYPE atom_todos gauge",
+            r#"atom_todos{status="pending"} 7"#,
+            r#"atom_todos{status="cancelled"} 0"#,
+            "# TYPE atom_theme_activations_total counter",
+            "atom_theme_activations_total 1",
+        ] {
+            assert!(lines.contains(&expected), "missing {:?} in:\n{}", expected, text);
+        }
+
+        // Every sample line is `name{labels} value` or `name value`
+        for line in lines.iter().filter(|line| !line.starts_with('#')) {
+            let (series, value) = line.rsplit_once(' ').expect("sample has a value");
+            assert!(series.starts_with("atom_"), "unprefixed series {:?}", series);
+            assert!(value.parse::<f64>().is_ok(), "bad value in {:?}", line);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use axum::Json;
+use platform_core::{ApiError, ProblemDetails};
+use utoipa::{
+    openapi::{
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        Deprecated,
+    },
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::{Config, SwaggerUi};
+
+use crate::{auth::SESSION_COOKIE, handlers, routes::API_V1};
+
+/// OpenAPI 3 description of every route, built from the handlers' `#[utoipa::path]`
+/// attributes and the `platform_core` schemas
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Atom Platform API",
+        description = "Todos, themes and settings for the Atom Platform. Errors use the `ApiError` body; send `Accept: application/problem+json` for RFC 7807 problem documents instead."
+    ),
+    paths(
+        crate::health::health_check,
+        crate::health::live,
+        crate::health::ready,
+        handlers::auth::login,
+        handlers::auth::logout,
+        handlers::auth::me,
+        handlers::users::list_users,
+        handlers::users::list_assignees,
+        handlers::users::create_user,
+        handlers::users::update_user_role,
+        handlers::tokens::list_tokens,
+        handlers::tokens::create_token,
+        handlers::tokens::revoke_token,
+        handlers::todos::list_todos,
+        handlers::todos::search_todos,
+        handlers::todos::get_todo,
+        handlers::todos::create_todo,
+        handlers::todos::update_todo,
+        handlers::todos::delete_todo,
+        handlers::todos::restore_todo,
+        handlers::todos::list_children,
+        handlers::todos::move_todo,
+        handlers::todos::todo_history,
+        handlers::todos::bulk_todos,
+        handlers::themes::list_themes,
+        handlers::themes::get_theme_with_settings,
+        handlers::themes::create_theme,
+        handlers::themes::update_theme,
+        handlers::themes::delete_theme,
+        handlers::themes::restore_theme,
+        handlers::themes::update_theme_setting,
+        handlers::component_settings::list_settings,
+        handlers::component_settings::get_setting,
+        handlers::component_settings::update_setting,
+        crate::events::events,
+    ),
+    components(schemas(ApiError, ProblemDetails)),
+    modifiers(&Authentication, &Deprecations),
+    security(("bearer" = []), ("session_cookie" = [])),
+    tags(
+        (name = "auth", description = "Sessions"),
+        (name = "users", description = "Accounts and roles"),
+        (name = "tokens", description = "Personal access tokens"),
+        (name = "todos", description = "Todos, subtasks, history and bulk actions"),
+        (name = "themes", description = "Themes and their settings"),
+        (name = "settings", description = "Component settings"),
+        (name = "events", description = "Live change feed"),
+        (name = "health", description = "Liveness and readiness probes"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the two ways to authenticate: a bearer token or the session cookie
+struct Authentication;
+
+impl Modify for Authentication {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("Session token or `atom_pat_` personal access token"))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "session_cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(SESSION_COOKIE))),
+        );
+    }
+}
+
+/// Marks the component settings operations deprecated in favour of themes
+struct Deprecations;
+
+impl Modify for Deprecations {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let prefix = format!("{}/settings", API_V1);
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if !path.starts_with(&prefix) {
+                continue;
+            }
+            for operation in [&mut item.get, &mut item.put].into_iter().flatten() {
+                operation.deprecated = Some(Deprecated::True);
+            }
+        }
+    }
+}
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Swagger UI for [`openapi_json`] at `{prefix}/docs`
+///
+/// The UI's assets are bundled into the binary, so the page loads nothing
+/// from third parties.
+pub fn docs(prefix: &str) -> SwaggerUi {
+    SwaggerUi::new(format!("{}/docs", prefix))
+        .config(Config::new([format!("{}/openapi.json", prefix)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiDoc;
+    use crate::{db::Database, routes, test_support};
+    use std::collections::BTreeSet;
+    use utoipa::OpenApi;
+
+    /// Routes that describe or monitor the API rather than being part of it
+    const UNDOCUMENTED: [&str; 2] = ["/api/v1/openapi.json", "/metrics"];
+
+    /// `(method, path)` for every route the router is built from, with
+    /// `:param` and `*param` segments written as `{param}` like in OpenAPI
+    fn registered_routes() -> BTreeSet<(String, String)> {
+        let config = test_support::config();
+        let db = Database::lazy(&config.database_url, config.database_acquire_timeout)
+            .expect("valid database URL");
+        routes::route_table(&test_support::state(&config, db))
+            .into_iter()
+            .filter(|(_, path)| !UNDOCUMENTED.contains(&path.as_str()))
+            .map(|(method, path)| {
+                let path = path
+                    .split('/')
+                    .map(|segment| match segment.strip_prefix([':', '*']) {
+                        Some(name) => format!("{{{}}}", name),
+                        None => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                (method.as_str().to_lowercase(), path)
+            })
+            .collect()
+    }
+
+    /// `(method, path)` for every operation in the generated document
+    fn documented_routes() -> BTreeSet<(String, String)> {
+        let doc = ApiDoc::openapi();
+        let mut routes = BTreeSet::new();
+        for (path, item) in doc.paths.paths {
+            let operations = [
+                ("get", item.get.is_some()),
+                ("post", item.post.is_some()),
+                ("put", item.put.is_some()),
+                ("delete", item.delete.is_some()),
+                ("patch", item.patch.is_some()),
+            ];
+            for (method, present) in operations {
+                if present {
+                    routes.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        routes
+    }
+
+    // The state's pool needs a runtime even though it never connects
+    #[tokio::test]
+    async fn every_route_is_documented() {
+        let registered = registered_routes();
+        assert!(!registered.is_empty(), "no routes in the route table");
+        let documented = documented_routes();
+
+        let undocumented: Vec<_> = registered.difference(&documented).collect();
+        assert!(
+            undocumented.is_empty(),
+            "routes without a #[utoipa::path] listed in ApiDoc: {:?}",
+            undocumented
+        );
+
+        let stale: Vec<_> = documented.difference(&registered).collect();
+        assert!(
+            stale.is_empty(),
+            "documented operations that no route serves: {:?}",
+            stale
+        );
+    }
+}
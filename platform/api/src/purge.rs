@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use crate::db::Database;
+
+/// How often the purge job looks for expired trash
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Starts a background task that purges expired trash, sessions and
+/// idempotency keys every [`PURGE_INTERVAL`]
+pub fn spawn(db: Database, retention_days: u32) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_deleted(&db, retention_days).await {
+                Ok((0, 0)) => {}
+                Ok((todos, themes)) => {
+                    tracing::info!("Purged {} todos and {} themes from the trash", todos, themes);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to purge trash: {}", e);
+                }
+            }
+            if let Err(e) = purge_expired_sessions(&db).await {
+                tracing::error!("Failed to purge expired sessions: {}", e);
+            }
+            if let Err(e) = purge_expired_idempotency_keys(&db).await {
+                tracing::error!("Failed to purge expired idempotency keys: {}", e);
+            }
+        }
+    });
+}
+
+/// Hard-deletes todos and themes soft-deleted more than `retention_days` ago
+///
+/// Returns the number of purged todos and themes. Todo history is kept.
+pub async fn purge_deleted(db: &Database, retention_days: u32) -> Result<(u64, u64), sqlx::Error> {
+    let days = retention_days as i32;
+    let mut tx = db.pool().begin().await?;
+
+    let todos = sqlx::query!(
+        "DELETE FROM todos WHERE deleted_at < NOW() - make_interval(days => $1)",
+        days
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let themes = sqlx::query!(
+        "DELETE FROM themes WHERE deleted_at < NOW() - make_interval(days => $1)",
+        days
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+
+    Ok((todos, themes))
+}
+
+/// Deletes login sessions past their expiry
+pub async fn purge_expired_sessions(db: &Database) -> Result<u64, sqlx::Error> {
+    let sessions = sqlx::query!("DELETE FROM sessions WHERE expires_at <= NOW()")
+        .execute(db.pool())
+        .await?
+        .rows_affected();
+
+    Ok(sessions)
+}
+
+/// Deletes stored `Idempotency-Key` responses past their expiry
+pub async fn purge_expired_idempotency_keys(db: &Database) -> Result<u64, sqlx::Error> {
+    let keys = sqlx::query!("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+        .execute(db.pool())
+        .await?
+        .rows_affected();
+
+    Ok(keys)
+}
@@ -0,0 +1,351 @@
+//! Per-client token-bucket rate limiting
+//!
+//! Each client gets a bucket holding a minute's worth of requests that refills
+//! continuously. Signed-in clients are keyed by API token, or by user for
+//! sessions; anonymous ones by IP address. Routes with an override have their
+//! own bucket per client; all other routes share one.
+//!
+//! The limit runs before authentication. Each request is first taken from
+//! its address's bucket, so a flood of made-up tokens is refused without a
+//! database lookup, and moved to its credential's bucket once that checks out.
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    auth::{CurrentUser, Credential},
+    config::Config,
+    error::AppError,
+    routes::API_V1,
+    state::AppState,
+};
+
+/// How often, in checked requests, buckets that have refilled completely are dropped
+const PRUNE_EVERY: u64 = 4096;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+    checks: Arc<AtomicU64>,
+    /// Requests per minute on routes without an override; 0 is unlimited
+    per_minute: u32,
+    /// Overrides keyed by `METHOD /path`, with paths relative to the API version
+    routes: BTreeMap<String, u32>,
+    trusted_proxies: usize,
+}
+
+impl RateLimiter {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            buckets: Arc::default(),
+            checks: Arc::default(),
+            per_minute: config.rate_limit_per_minute,
+            routes: config.rate_limit_routes.clone(),
+            trusted_proxies: config.trusted_proxies,
+        }
+    }
+
+    /// Takes one request from `key`'s bucket, or says how long until one is available
+    fn take(&self, key: &str, per_minute: u32) -> Result<(), Duration> {
+        let capacity = f64::from(per_minute);
+        let per_second = capacity / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        if self.checks.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_EVERY) {
+            // A bucket idle for a minute is full again, the same as a new one
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated) < Duration::from_secs(60));
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+
+    /// Returns a request taken from `key`'s bucket
+    fn give_back(&self, key: &str, per_minute: u32) {
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        if let Some(bucket) = buckets.get_mut(key) {
+            bucket.tokens = (bucket.tokens + 1.0).min(f64::from(per_minute));
+        }
+    }
+
+    /// The signed-in client, as a bucket key, once authentication has run
+    fn credential(request: &Request) -> Option<String> {
+        match (
+            request.extensions().get::<CurrentUser>(),
+            request.extensions().get::<Credential>(),
+        ) {
+            (_, Some(Credential::ApiToken { id, .. })) => Some(format!("token:{}", id)),
+            (Some(CurrentUser(user)), Some(Credential::Session)) => Some(format!("user:{}", user.id)),
+            _ => None,
+        }
+    }
+
+    /// Where the request comes from, as a bucket key
+    fn address(&self, request: &Request) -> String {
+        let ip = self.forwarded_for(request).or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip())
+        });
+        match ip {
+            Some(ip) => format!("ip:{}", ip),
+            None => "ip:unknown".to_string(),
+        }
+    }
+
+    /// The `X-Forwarded-For` entry added by the outermost trusted proxy
+    ///
+    /// Entries further left are whatever the client sent, so they are never
+    /// used. A header too short to have passed every proxy is ignored.
+    fn forwarded_for(&self, request: &Request) -> Option<IpAddr> {
+        let hops = self.trusted_proxies.checked_sub(1)?;
+        let entries: Vec<&str> = request
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect();
+        entries.iter().rev().nth(hops)?.trim().parse().ok()
+    }
+}
+
+/// `METHOD /path` for the matched route, without the `/api/v1` or `/api` prefix
+fn route_key(request: &Request) -> Option<String> {
+    let path = request.extensions().get::<MatchedPath>()?.as_str();
+    let path = path
+        .strip_prefix(API_V1)
+        .or_else(|| path.strip_prefix("/api"))
+        .unwrap_or(path);
+    Some(format!("{} {}", request.method(), path))
+}
+
+/// Route layer answering `429` once the client's bucket is empty
+///
+/// Runs outside the authentication layer. The request is charged to its
+/// address before its credential is looked up, then moved to the token's or
+/// user's bucket if the credential is valid. Requests whose credentials are
+/// missing or rejected stay on the address and are left for the
+/// authentication layer to refuse.
+pub async fn limit(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let limiter = &state.rate_limiter;
+    let route = route_key(&request);
+    let (bucket, per_minute) = match route.as_ref().and_then(|route| limiter.routes.get(route)) {
+        Some(&per_minute) => (route.clone(), per_minute),
+        None => (None, limiter.per_minute),
+    };
+    if per_minute == 0 {
+        return next.run(request).await;
+    }
+
+    let key = |client: String| match &bucket {
+        Some(route) => format!("{} {}", client, route),
+        None => client,
+    };
+
+    let address = key(limiter.address(&request));
+    if let Err(wait) = limiter.take(&address, per_minute) {
+        return AppError::RateLimited(wait).into_response();
+    }
+
+    let (mut parts, body) = request.into_parts();
+    // Stored in the request on success, so the authentication layer does not look it up again
+    let _ = CurrentUser::from_request_parts(&mut parts, &state).await;
+    let request = Request::from_parts(parts, body);
+
+    if let Some(client) = RateLimiter::credential(&request) {
+        limiter.give_back(&address, per_minute);
+        if let Err(wait) = limiter.take(&key(client), per_minute) {
+            return AppError::RateLimited(wait).into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        extract::ConnectInfo,
+        http::{header, Request, Response, StatusCode},
+        Router,
+    };
+    use sqlx::PgPool;
+    use std::{
+        fmt,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+    use tower::Service;
+    use tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    use crate::{db::Database, test_support};
+
+    const LIMIT: u32 = 3;
+
+    /// `LIMIT` requests a minute for every client
+    fn app() -> Router {
+        test_support::app(&config())
+    }
+
+    fn config() -> crate::config::Config {
+        let mut config = test_support::config();
+        config.rate_limit_per_minute = LIMIT;
+        config
+    }
+
+    async fn get(app: &mut Router, from: [u8; 4], token: Option<&str>) -> Response<Body> {
+        let mut request = Request::get("/api/v1/todos");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        send(app, from, request.body(Body::empty()).unwrap()).await
+    }
+
+    async fn send(app: &mut Router, from: [u8; 4], mut request: Request<Body>) -> Response<Body> {
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((from, 40000))));
+        app.call(request).await.unwrap()
+    }
+
+    fn retry_after(response: &Response<Body>) -> u64 {
+        response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    /// Counts the statements sqlx runs against the `sessions` table
+    #[derive(Clone, Default)]
+    struct SessionLookups(Arc<AtomicUsize>);
+
+    impl<S: Subscriber> Layer<S> for SessionLookups {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let mut statement = Statement(false);
+            event.record(&mut statement);
+            if event.metadata().target() == "sqlx::query" && statement.0 {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Whether an event's `db.statement` looks up a session token
+    struct Statement(bool);
+
+    impl Visit for Statement {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "db.statement" {
+                self.0 |= format!("{:?}", value).contains("sessions.token_hash");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_requests_without_credentials_by_address() {
+        let mut app = app();
+        for _ in 0..LIMIT {
+            let response = get(&mut app, [192, 0, 2, 1], None).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = get(&mut app, [192, 0, 2, 1], None).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        // One request comes back every 60 / LIMIT seconds
+        assert!((1..=60 / u64::from(LIMIT)).contains(&retry_after(&response)));
+
+        // Other addresses have buckets of their own
+        let response = get(&mut app, [192, 0, 2, 2], None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn refuses_rejected_tokens_before_looking_them_up(pool: PgPool) {
+        let mut app = test_support::app_with(&config(), Database::from_pool(pool));
+        let lookups = SessionLookups::default();
+        let _guard = tracing_subscriber::registry()
+            .with(lookups.clone())
+            .set_default();
+
+        for attempt in 0..10 {
+            let token = format!("made-up-{}", attempt);
+            let response = get(&mut app, [192, 0, 2, 1], Some(&token)).await;
+            let expected = if attempt < LIMIT {
+                StatusCode::UNAUTHORIZED
+            } else {
+                StatusCode::TOO_MANY_REQUESTS
+            };
+            assert_eq!(response.status(), expected);
+        }
+
+        // One session lookup for each request the address had room for
+        assert_eq!(lookups.0.load(Ordering::SeqCst), LIMIT as usize);
+    }
+
+    #[tokio::test]
+    async fn keys_forwarded_requests_by_the_address_the_proxy_saw() {
+        let mut config = config();
+        config.trusted_proxies = 1;
+        let mut app = test_support::app(&config);
+        let forwarded = |chain: &str| {
+            Request::get("/api/v1/todos")
+                .header("x-forwarded-for", chain)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // A new made-up address on every request, in front of the one the proxy appended
+        for attempt in 0..LIMIT {
+            let chain = format!("198.51.100.{}, 203.0.113.7", attempt);
+            let response = send(&mut app, [10, 0, 0, 1], forwarded(&chain)).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        let response = send(&mut app, [10, 0, 0, 1], forwarded("198.51.100.99, 203.0.113.7")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Clients the proxy saw at other addresses have buckets of their own
+        let response = send(&mut app, [10, 0, 0, 1], forwarded("198.51.100.99, 203.0.113.8")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,33 +1,266 @@
 use axum::{
-    routing::{get, post},
+    extract::{DefaultBodyLimit, Request},
+    handler::Handler,
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{self, MethodFilter, Route},
     Router,
 };
+use platform_core::Permission;
+use std::convert::Infallible;
+use tower::{Layer, Service};
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
+
+use crate::{
+    auth::{CurrentUser, RequirePermission},
+    config::Config,
+    error::finish_errors,
+    events,
+    health,
+    idempotency,
+    metrics,
+    openapi,
+    rate_limit,
+    handlers::{auth, todos, component_settings, themes, tokens, users},
+    state::AppState,
+    telemetry,
+};
+
+/// Path prefix of the current API version
+pub const API_V1: &str = "/api/v1";
 
-use crate::{db::Database, handlers::{todos, component_settings, themes}};
+pub fn create_router(state: AppState, config: &Config) -> Router {
+    let v1 = v1_routes(&state).router;
 
-pub fn create_router(db: Database) -> Router {
     Router::new()
-        .route("/health", get(health_check))
-        .route("/api/todos", get(todos::list_todos).post(todos::create_todo))
-        .route(
-            "/api/todos/:id",
-            get(todos::get_todo)
-                .put(todos::update_todo)
-                .delete(todos::delete_todo),
+        .nest(API_V1, v1.clone())
+        // The unversioned paths from before versioning, kept for existing clients
+        .nest("/api", v1)
+        // Browsable reference for `openapi.json`, under both prefixes
+        .merge(openapi::docs(API_V1))
+        .merge(openapi::docs("/api"))
+        .layer(DefaultBodyLimit::max(config.max_body_bytes))
+        .layer(TimeoutLayer::new(config.request_timeout))
+        .layer(middleware::from_fn(finish_errors))
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            metrics::track,
+        ))
+        // Added after the layers above so failing probes keep their own bodies,
+        // and neither probes nor scrapes are counted as API requests
+        .merge(probes().router)
+        // One span per request, carrying the request id set by the layers below
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(telemetry::make_span)
+                .on_request(())
+                .on_response(telemetry::on_response),
         )
-        .route("/api/settings", get(component_settings::list_settings))
-        .route(
-            "/api/settings/:id",
-            get(component_settings::get_setting)
-                .put(component_settings::update_setting),
-        )
-        .route("/api/themes", get(themes::list_themes).post(themes::create_theme))
-        .route("/api/themes/:id", get(themes::get_theme_with_settings).put(themes::update_theme).delete(themes::delete_theme))
-        .route("/api/themes/:theme_id/settings/:setting_id", axum::routing::put(themes::update_theme_setting))
-        .with_state(db)
+        // Reuses an incoming `x-request-id` or makes one, and echoes it back
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .with_state(state)
+}
+
+/// Every route of [`create_router`] except the docs UI, as `(method, path)`,
+/// with the versioned routes under [`API_V1`] only
+#[cfg(test)]
+pub fn route_table(state: &AppState) -> Vec<(Method, String)> {
+    let v1 = v1_routes(state)
+        .table
+        .into_iter()
+        .map(|(method, path)| (method, format!("{}{}", API_V1, path)));
+    v1.chain(probes().table).collect()
+}
+
+/// A router that lists its routes, so tests can check them against the
+/// OpenAPI document
+struct Routes {
+    router: Router<AppState>,
+    table: Vec<(Method, String)>,
+}
+
+impl Routes {
+    fn new() -> Self {
+        Self {
+            router: Router::new(),
+            table: Vec::new(),
+        }
+    }
+
+    /// Routes `method` requests for `path` to `handler`
+    fn on<H, T>(mut self, method: Method, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        let filter = MethodFilter::try_from(method.clone()).expect("a method axum can route");
+        self.router = self.router.route(path, routing::on(filter, handler));
+        self.table.push((method, path.to_string()));
+        self
+    }
+
+    fn get<H: Handler<T, AppState>, T: 'static>(self, path: &str, handler: H) -> Self {
+        self.on(Method::GET, path, handler)
+    }
+
+    fn post<H: Handler<T, AppState>, T: 'static>(self, path: &str, handler: H) -> Self {
+        self.on(Method::POST, path, handler)
+    }
+
+    fn put<H: Handler<T, AppState>, T: 'static>(self, path: &str, handler: H) -> Self {
+        self.on(Method::PUT, path, handler)
+    }
+
+    fn delete<H: Handler<T, AppState>, T: 'static>(self, path: &str, handler: H) -> Self {
+        self.on(Method::DELETE, path, handler)
+    }
+
+    /// [`Router::route_layer`], for the routes added so far
+    fn route_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.router = self.router.route_layer(layer);
+        self
+    }
+
+    fn merge(mut self, other: Routes) -> Self {
+        self.router = self.router.merge(other.router);
+        self.table.extend(other.table);
+        self
+    }
+}
+
+/// Health probes and the metrics scrape, outside the API's middleware
+fn probes() -> Routes {
+    Routes::new()
+        .get("/metrics", metrics::metrics)
+        .get("/health", health::health_check)
+        .get("/health/live", health::live)
+        .get("/health/ready", health::ready)
 }
 
-async fn health_check() -> &'static str {
-    "OK"
+/// Version 1 of the API, relative to [`API_V1`]
+fn v1_routes(state: &AppState) -> Routes {
+    // Replays the response to a retried create with the same `Idempotency-Key`
+    let idempotent = middleware::from_fn_with_state(state.clone(), idempotency::idempotent);
+
+    // Any signed-in user, and API tokens with the read scope
+    let read = Routes::new()
+        .post("/auth/logout", auth::logout)
+        .get("/auth/me", auth::me)
+        .get("/todos", todos::list_todos)
+        .get("/todos/search", todos::search_todos)
+        .get("/todos/:id", todos::get_todo)
+        .get("/todos/:id/history", todos::todo_history)
+        .get("/todos/:id/children", todos::list_children)
+        .get("/themes", themes::list_themes)
+        .get("/themes/:id", themes::get_theme_with_settings)
+        // Everyone needs names for assignees, but not emails or roles
+        .get("/assignees", users::list_assignees)
+        // Each user manages their own tokens
+        .get("/tokens", tokens::list_tokens)
+        .post("/tokens", tokens::create_token)
+        .delete("/tokens/:id", tokens::revoke_token)
+        .route_layer(RequirePermission(Permission::Read));
+
+    let edit_todos = Routes::new()
+        .post("/todos", todos::create_todo.layer(idempotent.clone()))
+        .put("/todos/:id", todos::update_todo)
+        .delete("/todos/:id", todos::delete_todo)
+        .put("/todos/:id/parent", todos::move_todo)
+        .route_layer(RequirePermission(Permission::EditTodos));
+
+    let manage_todos = Routes::new()
+        .post("/todos/bulk", todos::bulk_todos.layer(idempotent.clone()))
+        .post("/todos/:id/restore", todos::restore_todo)
+        .route_layer(RequirePermission(Permission::ManageTodos));
+
+    let manage_settings = Routes::new()
+        .post("/themes", themes::create_theme.layer(idempotent))
+        .put("/themes/:id", themes::update_theme)
+        .delete("/themes/:id", themes::delete_theme)
+        .post("/themes/:id/restore", themes::restore_theme)
+        .put("/themes/:theme_id/settings/:setting_id", themes::update_theme_setting)
+        .route_layer(RequirePermission(Permission::ManageSettings));
+
+    let manage_users = Routes::new()
+        .get("/users", users::list_users)
+        .post("/users", users::create_user)
+        .put("/users/:id/role", users::update_user_role)
+        .route_layer(RequirePermission(Permission::ManageUsers));
+
+    // Superseded by themes; every response says so and when the routes go away
+    let component_settings = Routes::new()
+        .get("/settings", component_settings::list_settings)
+        .get("/settings/:id", component_settings::get_setting)
+        .route_layer(RequirePermission(Permission::Read))
+        .merge(
+            Routes::new()
+                .put("/settings/:id", component_settings::update_setting)
+                .route_layer(RequirePermission(Permission::ManageSettings)),
+        )
+        .route_layer(middleware::map_response(component_settings_deprecation));
+
+    // Long-lived, so kept out of the rate limit. Browsers, whose `EventSource`
+    // cannot send `Authorization`, sign in with the session cookie
+    let change_feed = Routes::new()
+        .get("/events", events::events)
+        .route_layer(RequirePermission(Permission::Read))
+        .route_layer(middleware::from_extractor_with_state::<CurrentUser, _>(
+            state.clone(),
+        ));
+
+    // Everything above requires a signed-in user; the permission layers run after this one
+    let protected = read
+        .merge(edit_todos)
+        .merge(manage_todos)
+        .merge(manage_settings)
+        .merge(manage_users)
+        .merge(component_settings)
+        .route_layer(middleware::from_extractor_with_state::<CurrentUser, _>(
+            state.clone(),
+        ))
+        // Outside the authentication layer so requests it refuses are limited too
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit::limit));
+
+    Routes::new()
+        .post("/auth/login", auth::login)
+        .get("/openapi.json", openapi::openapi_json)
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit::limit))
+        .merge(protected)
+        .merge(change_feed)
 }
 
+/// When `/settings` was deprecated, as an RFC 9745 date (2026-10-17)
+const COMPONENT_SETTINGS_DEPRECATED: &str = "@1792195200";
+/// When `/settings` will be removed, as an RFC 8594 HTTP date
+const COMPONENT_SETTINGS_SUNSET: &str = "Fri, 30 Apr 2027 00:00:00 GMT";
+
+/// Adds `Deprecation`, `Sunset` and a `Link` to the themes API that replaces them
+async fn component_settings_deprecation(mut response: Response) -> Response {
+    let headers = response.headers_mut();
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static(COMPONENT_SETTINGS_DEPRECATED),
+    );
+    headers.insert(
+        HeaderName::from_static("sunset"),
+        HeaderValue::from_static(COMPONENT_SETTINGS_SUNSET),
+    );
+    headers.insert(
+        header::LINK,
+        HeaderValue::from_static("</api/v1/themes>; rel=\"successor-version\""),
+    );
+    response
+}
@@ -0,0 +1,35 @@
+//! Graceful shutdown on SIGTERM or Ctrl-C
+
+use std::time::Duration;
+#[cfg(unix)]
+use tokio::signal::unix::SignalKind;
+
+/// Resolves when the process is asked to stop
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Resolves `timeout` after [`signal`], when requests still in flight are dropped
+pub async fn deadline(timeout: Duration) {
+    signal().await;
+    tokio::time::sleep(timeout).await;
+}
@@ -0,0 +1,41 @@
+use axum::extract::FromRef;
+
+use crate::{db::Database, events::EventBus, metrics::Metrics, rate_limit::RateLimiter};
+
+/// State shared by every handler
+///
+/// Handlers that only need the database keep extracting `State<Database>`.
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Database,
+    /// How long a login session stays valid
+    pub session_ttl: chrono::Duration,
+    /// Whether the session cookie is marked `Secure`
+    pub secure_cookies: bool,
+    /// How long a response stored under an `Idempotency-Key` is replayed
+    pub idempotency_ttl: chrono::Duration,
+    /// Requests are dropped after this, so an older unanswered claim on an
+    /// `Idempotency-Key` is abandoned
+    pub request_timeout: std::time::Duration,
+    pub metrics: Metrics,
+    pub rate_limiter: RateLimiter,
+    pub events: EventBus,
+}
+
+impl FromRef<AppState> for Database {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for Metrics {
+    fn from_ref(state: &AppState) -> Self {
+        state.metrics.clone()
+    }
+}
+
+impl FromRef<AppState> for EventBus {
+    fn from_ref(state: &AppState) -> Self {
+        state.events.clone()
+    }
+}
@@ -0,0 +1,109 @@
+//! Log output and per-request spans
+
+use axum::{
+    extract::{MatchedPath, Request},
+    response::Response,
+};
+use std::time::Duration;
+use tower_http::request_id::RequestId;
+use tracing::{field, Span};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+use crate::config::LogFormat;
+
+/// Filter used when `RUST_LOG` is not set; `sqlx=warn` keeps only slow statements
+const DEFAULT_FILTER: &str = "platform_api=debug,tower_http=debug,sqlx=warn";
+
+/// Installs the global subscriber, writing `format` to stdout
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| DEFAULT_FILTER.into());
+    let registry = tracing_subscriber::registry().with(filter);
+    match format {
+        LogFormat::Text => registry.with(fmt::layer()).init(),
+        // One object per line; fields of the request span are under `span`
+        LogFormat::Json => registry
+            .with(fmt::layer().json().with_current_span(true).with_span_list(false))
+            .init(),
+    }
+}
+
+/// Span around one request; `status` and `latency_ms` are filled in by [`on_response`]
+pub fn make_span(request: &Request) -> Span {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or_else(|| request.uri().path());
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        route,
+        request_id,
+        status = field::Empty,
+        latency_ms = field::Empty,
+    )
+}
+
+pub fn on_response(response: &Response, latency: Duration, span: &Span) {
+    let status = response.status().as_u16();
+    let latency_ms = latency.as_secs_f64() * 1000.0;
+    span.record("status", status);
+    span.record("latency_ms", latency_ms);
+    tracing::info!(status, latency_ms, "finished request");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support;
+    use axum::{body::Body, extract::ConnectInfo, http::Request};
+    use std::{
+        io,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    };
+    use tower::Service;
+
+    /// Log output shared with the test
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn request_logs_leave_out_the_query_string() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut app = test_support::app(&test_support::config());
+        let mut request = Request::get("/api/v1/events?access_token=secret-token")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 1))));
+        app.call(request).await.unwrap();
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains(r#"route="/api/v1/events""#), "no request span in {}", logs);
+        assert!(!logs.contains("secret-token"), "query string logged in {}", logs);
+    }
+}
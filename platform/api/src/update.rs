@@ -0,0 +1,115 @@
+use platform_core::Patch;
+use sqlx::{Encode, Postgres, QueryBuilder, Type};
+
+/// Builds a typed `UPDATE ... SET` statement from optional and [`Patch`] fields
+///
+/// Each value is bound with its own SQL type, so enums and timestamps need no
+/// casts. `updated_at` is always bumped. Table and column names are pushed as
+/// raw SQL and must be static identifiers, never user input.
+pub struct UpdateBuilder<'args> {
+    query: QueryBuilder<'args, Postgres>,
+    changes: usize,
+}
+
+impl<'args> UpdateBuilder<'args> {
+    pub fn new(table: &'static str) -> Self {
+        let mut query = QueryBuilder::new("UPDATE ");
+        query.push(table).push(" SET updated_at = NOW()");
+        Self { query, changes: 0 }
+    }
+
+    /// Sets `column` when `value` is `Some`, otherwise leaves it unchanged
+    pub fn set<T>(&mut self, column: &'static str, value: Option<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres> + Send,
+    {
+        if let Some(value) = value {
+            self.assign(column).push_bind(value);
+        }
+        self
+    }
+
+    /// Sets or clears `column` according to `patch`
+    pub fn patch<T>(&mut self, column: &'static str, patch: Patch<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres> + Send,
+    {
+        match patch {
+            Patch::Absent => {}
+            Patch::Null => {
+                self.assign(column).push("NULL");
+            }
+            Patch::Value(value) => {
+                self.assign(column).push_bind(value);
+            }
+        }
+        self
+    }
+
+    /// Starts `column = ` and returns the query so the caller can push an expression
+    pub fn assign(&mut self, column: &'static str) -> &mut QueryBuilder<'args, Postgres> {
+        self.changes += 1;
+        self.query.push(", ").push(column).push(" = ");
+        &mut self.query
+    }
+
+    /// Whether any column besides `updated_at` is being set
+    pub fn has_changes(&self) -> bool {
+        self.changes > 0
+    }
+
+    /// Ends the SET list and opens the WHERE clause
+    ///
+    /// Push the conditions and a RETURNING clause onto the returned query.
+    pub fn finish(mut self) -> QueryBuilder<'args, Postgres> {
+        self.query.push(" WHERE ");
+        self.query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UpdateBuilder;
+    use platform_core::Patch;
+
+    #[test]
+    fn nothing_set_has_no_changes() {
+        let mut update = UpdateBuilder::new("todos");
+        update
+            .set::<String>("title", None)
+            .patch::<String>("description", Patch::Absent);
+        assert!(!update.has_changes());
+        assert_eq!(update.finish().sql(), "UPDATE todos SET updated_at = NOW() WHERE ");
+    }
+
+    #[test]
+    fn set_value_is_a_change() {
+        let mut update = UpdateBuilder::new("todos");
+        update.set("title", Some("Milk".to_string()));
+        assert!(update.has_changes());
+        assert_eq!(
+            update.finish().sql(),
+            "UPDATE todos SET updated_at = NOW(), title = $1 WHERE "
+        );
+    }
+
+    #[test]
+    fn cleared_and_patched_values_are_changes() {
+        let mut update = UpdateBuilder::new("todos");
+        update
+            .patch::<String>("description", Patch::Null)
+            .patch("due_date", Patch::Value(3));
+        assert!(update.has_changes());
+        assert_eq!(
+            update.finish().sql(),
+            "UPDATE todos SET updated_at = NOW(), description = NULL, due_date = $1 WHERE "
+        );
+    }
+
+    #[test]
+    fn assigned_expression_is_a_change() {
+        let mut update = UpdateBuilder::new("todos");
+        update.assign("version").push("version + 1");
+        assert!(update.has_changes());
+    }
+}
@@ -0,0 +1,36 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+    Json,
+};
+use platform_core::Validate;
+use serde::de::DeserializeOwned;
+
+use crate::error::AppError;
+
+/// JSON request body that passed its [`Validate`] rules
+///
+/// Invalid bodies are answered with `422` and a `validation_failed` error
+/// listing every bad field, before the handler runs. Bodies that are not JSON
+/// or do not match the type are rejected as with [`Json`].
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        value
+            .validate()
+            .map_err(|fields| AppError::Validation(fields).into_response())?;
+        Ok(ValidJson(value))
+    }
+}